@@ -0,0 +1,282 @@
+//! Synthetic NEAR chains for tests.
+//!
+//! Generates a chain of headers with consistent epoch ids, `next_bp_hash`es
+//! and block merkle roots, plus the light client blocks a client syncs
+//! through: the last block of each epoch, carrying the next block producers
+//! and signed by the current ones.
+
+use ed25519_dalek::{Signer, SigningKey};
+use light_client_core::Protocol;
+use types::{
+    bps_hash, combine_hash, hash_borsh, merklize, AccountId, Balance, BlockHeaderInnerLiteView,
+    BlockHeight, EpochId, Hash, Header, LightClientBlockView, MerklePath, PublicKey, Signature,
+    TrustedState, ValidatorStake, ValidatorStakeView,
+};
+
+const GENESIS_TIMESTAMP_NANOSEC: u64 = 1_600_000_000_000_000_000;
+const BLOCK_TIME_NANOSEC: u64 = 1_000_000_000;
+
+/// How stake is spread over the seats of an epoch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StakeDistribution {
+    Uniform(Balance),
+    /// `base + step * seat`.
+    Linear {
+        base: Balance,
+        step: Balance,
+    },
+    /// One stake per seat.
+    Explicit(Vec<Balance>),
+}
+
+impl StakeDistribution {
+    pub fn stake(&self, seat: usize) -> Balance {
+        match self {
+            Self::Uniform(stake) => *stake,
+            Self::Linear { base, step } => base + step * seat as Balance,
+            Self::Explicit(stakes) => stakes[seat],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainConfig {
+    /// Seed for validator keys and the contents of each block.
+    pub seed: u64,
+    pub num_validators: usize,
+    pub stake: StakeDistribution,
+    /// Number of epochs after genesis, each with one light client block.
+    pub epochs: usize,
+    /// Number of blocks in each epoch.
+    pub epoch_length: BlockHeight,
+    /// Number of validators replaced by new ones at each epoch boundary.
+    pub rotation: usize,
+    /// Seats that don't approve any light client block.
+    pub offline: Vec<usize>,
+}
+
+impl Default for ChainConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            num_validators: 4,
+            stake: StakeDistribution::Uniform(1_000_000),
+            epochs: 3,
+            epoch_length: 5,
+            rotation: 0,
+            offline: Vec::new(),
+        }
+    }
+}
+
+/// A validator whose key is derived from the chain seed.
+#[derive(Debug, Clone)]
+pub struct Validator {
+    pub account_id: AccountId,
+    signing_key: SigningKey,
+}
+
+impl Validator {
+    pub fn from_seed(seed: u64, index: usize) -> Self {
+        Self {
+            account_id: format!("validator-{}.near", index),
+            signing_key: SigningKey::from_bytes(&hash_borsh((seed, index as u64))),
+        }
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.signing_key.verifying_key().to_bytes()
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        Signature(self.signing_key.sign(message))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Chain {
+    pub config: ChainConfig,
+    /// Every validator that holds a seat in some epoch.
+    pub validators: Vec<Validator>,
+    /// Headers by height, from genesis.
+    pub headers: Vec<Header>,
+    /// Light client blocks of epochs `1..=epochs`.
+    pub light_client_blocks: Vec<LightClientBlockView>,
+}
+
+impl Chain {
+    pub fn generate(config: ChainConfig) -> Self {
+        let num_epochs = config.epochs + 1;
+        // Block producers of each epoch, with one past the last epoch to
+        // commit to in `next_bp_hash`
+        let validators = (0..config.num_validators + (num_epochs + 1) * config.rotation)
+            .map(|index| Validator::from_seed(config.seed, index))
+            .collect();
+        let mut chain = Self {
+            config,
+            validators,
+            headers: Vec::new(),
+            light_client_blocks: Vec::new(),
+        };
+
+        // Epoch `k + 1` is identified by the hash of the last block of epoch
+        // `k - 1`, as in nearcore. The first two have no such block.
+        let mut epoch_ids: Vec<EpochId> = vec![EpochId::default(), hash_borsh(chain.config.seed)];
+        let mut block_hashes = Vec::new();
+
+        // One block past the last epoch, for the last `next_block_inner_hash`
+        let num_blocks = num_epochs as BlockHeight * chain.config.epoch_length + 1;
+        for height in 0..num_blocks {
+            let epoch = (height / chain.config.epoch_length) as usize;
+            if epoch_ids.len() < epoch + 2 {
+                epoch_ids.push(block_hashes[height as usize - 1]);
+            }
+
+            let (block_merkle_root, _) = merklize(&block_hashes);
+            let header = Header {
+                prev_block_hash: block_hashes.last().copied().unwrap_or_default(),
+                inner_rest_hash: hash_borsh(("inner_rest", chain.config.seed, height)),
+                inner_lite: BlockHeaderInnerLiteView {
+                    height,
+                    epoch_id: epoch_ids[epoch],
+                    next_epoch_id: epoch_ids[epoch + 1],
+                    prev_state_root: hash_borsh(("state", chain.config.seed, height)),
+                    outcome_root: hash_borsh(("outcome", chain.config.seed, height)),
+                    timestamp: GENESIS_TIMESTAMP_NANOSEC + height * BLOCK_TIME_NANOSEC,
+                    timestamp_nanosec: GENESIS_TIMESTAMP_NANOSEC + height * BLOCK_TIME_NANOSEC,
+                    next_bp_hash: bps_hash(&chain.bps(epoch + 1)),
+                    block_merkle_root,
+                },
+            };
+            block_hashes.push(header.hash());
+            chain.headers.push(header);
+        }
+
+        for epoch in 1..num_epochs {
+            let height = (epoch + 1) as BlockHeight * chain.config.epoch_length - 1;
+            let block = chain.light_client_block(height);
+            chain.light_client_blocks.push(block);
+        }
+
+        chain
+    }
+
+    /// Block producers of `epoch`.
+    pub fn bps(&self, epoch: usize) -> Vec<ValidatorStake> {
+        let first = epoch * self.config.rotation;
+        (0..self.config.num_validators)
+            .map(|seat| {
+                let validator = &self.validators[first + seat];
+                ValidatorStake::new(
+                    validator.account_id.clone(),
+                    validator.public_key(),
+                    self.config.stake.stake(seat),
+                )
+            })
+            .collect()
+    }
+
+    /// The state of a client that trusts genesis.
+    pub fn genesis_state(&self) -> TrustedState {
+        TrustedState::new(self.headers[0].clone(), self.bps(1))
+    }
+
+    pub fn block_hash(&self, height: BlockHeight) -> Hash {
+        self.headers[height as usize].hash()
+    }
+
+    /// Path from the block at `height` to the block merkle root of the block
+    /// at `head_height`.
+    pub fn block_proof(&self, height: BlockHeight, head_height: BlockHeight) -> MerklePath {
+        let hashes: Vec<Hash> = self.headers[..head_height as usize]
+            .iter()
+            .map(Header::hash)
+            .collect();
+        let (_, mut paths) = merklize(&hashes);
+        paths.swap_remove(height as usize)
+    }
+
+    /// Sign the approvals of `block` with the online block producers of its
+    /// epoch, replacing any it had.
+    pub fn approve(&self, block: &mut LightClientBlockView) {
+        let epoch = (block.inner_lite.height / self.config.epoch_length) as usize;
+        let message = Protocol::reconstruct_approval_message(block).unwrap();
+        let first = epoch * self.config.rotation;
+
+        block.approvals_after_next = (0..self.config.num_validators)
+            .map(|seat| {
+                (!self.config.offline.contains(&seat))
+                    .then(|| Box::new(self.validators[first + seat].sign(&message)))
+            })
+            .collect();
+    }
+
+    fn light_client_block(&self, height: BlockHeight) -> LightClientBlockView {
+        let header = &self.headers[height as usize];
+        let next = &self.headers[height as usize + 1];
+        let epoch = (height / self.config.epoch_length) as usize;
+
+        let mut block = LightClientBlockView {
+            prev_block_hash: header.prev_block_hash,
+            next_block_inner_hash: combine_hash(&next.inner_lite.hash(), &next.inner_rest_hash),
+            inner_lite: header.inner_lite.clone(),
+            inner_rest_hash: header.inner_rest_hash,
+            next_bps: Some(
+                self.bps(epoch + 1)
+                    .into_iter()
+                    .map(|bp| ValidatorStakeView::new(bp.account_id, bp.public_key, bp.stake))
+                    .collect(),
+            ),
+            approvals_after_next: Vec::new(),
+        };
+        self.approve(&mut block);
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use light_client_core::Error;
+
+    #[test]
+    fn test_chain_syncs_from_genesis() {
+        let chain = Chain::generate(ChainConfig {
+            rotation: 1,
+            ..Default::default()
+        });
+
+        let state =
+            Protocol::sync_many(chain.genesis_state(), chain.light_client_blocks.clone()).unwrap();
+
+        let last = chain.light_client_blocks.last().unwrap();
+        assert_eq!(state.head.hash(), last.header().hash());
+        assert_eq!(state.epoch_bps, chain.bps(chain.config.epochs + 1));
+    }
+
+    #[test]
+    fn test_offline_signers() {
+        let chain = Chain::generate(ChainConfig {
+            offline: vec![0, 1],
+            ..Default::default()
+        });
+
+        let result = Protocol::sync_many(chain.genesis_state(), chain.light_client_blocks);
+        assert_eq!(result.unwrap_err(), Error::NotEnoughApprovedStake);
+    }
+
+    #[test]
+    fn test_block_proof() {
+        let chain = Chain::generate(ChainConfig::default());
+        let head = &chain.headers[12];
+
+        for height in 0..12 {
+            let proof = chain.block_proof(height, 12);
+            assert!(Protocol::verify_block(
+                &head.inner_lite.block_merkle_root,
+                proof.iter(),
+                &chain.block_hash(height),
+            ));
+        }
+    }
+}
@@ -1,9 +0,0 @@
-#[derive(Debug, PartialEq, Eq)]
-pub enum Error {
-    BlockAlreadyVerified,
-    BlockNotCurrentOrNextEpoch,
-    SignatureInvalid,
-    NotEnoughApprovedStake,
-    NextBpsInvalid,
-    ValidatorNotSigned,
-}
@@ -0,0 +1,20 @@
+#![cfg_attr(feature = "guest", no_std)]
+#![no_main]
+
+use light_client_core::prelude::*;
+use light_client_core::{BasicProof, Hash, Protocol};
+
+/// Bytes of input `inclusion_batch` takes, as its `max_input_size`: enough
+/// for the 128 proofs of a full verify request, at about 1.5 KB each. The
+/// attribute only takes a literal, so the host tests the two agree.
+pub const MAX_INPUT_SIZE: usize = 262144;
+
+#[jolt::provable(
+    stack_size = 8192,
+    memory_size = 33554432,
+    max_input_size = 262144,
+    max_output_size = 8192
+)]
+fn inclusion_batch(head_block_root: Hash, proofs: Vec<BasicProof>) -> Vec<(Hash, bool)> {
+    Protocol::verify_batch(&head_block_root, &proofs)
+}
@@ -0,0 +1,43 @@
+#![cfg_attr(feature = "guest", no_std)]
+#![no_main]
+
+use light_client_core::prelude::*;
+use light_client_core::{
+    LcProof, LightClientBlockView, Predicate, PredicateOutput, Protocol, ProtocolConfig,
+    SyncedOutcome, TrustedState, VerifiedOutcome,
+};
+
+#[jolt::provable(
+    stack_size = 8192,
+    memory_size = 4194304,
+    max_input_size = 10000,
+    max_output_size = 4096
+)]
+fn inclusion(proof: LcProof, config: ProtocolConfig) -> VerifiedOutcome {
+    Protocol::verify_inclusion(proof, config.num_shards).unwrap()
+}
+
+#[jolt::provable(
+    stack_size = 8192,
+    memory_size = 4194304,
+    max_input_size = 10000,
+    max_output_size = 1024
+)]
+fn inclusion_predicate(proof: LcProof, predicate: Predicate) -> PredicateOutput {
+    Protocol::inclusion_proof_verify_with(proof, &predicate).unwrap()
+}
+
+#[jolt::provable(
+    stack_size = 8192,
+    memory_size = 16777216,
+    max_input_size = 32768,
+    max_output_size = 4096
+)]
+fn sync_and_include(
+    state: TrustedState,
+    blocks: Vec<LightClientBlockView>,
+    proof: LcProof,
+    config: ProtocolConfig,
+) -> SyncedOutcome {
+    Protocol::sync_and_include(state, blocks, proof, config.num_shards).unwrap()
+}
@@ -0,0 +1,81 @@
+#![cfg_attr(feature = "guest", no_std)]
+#![no_main]
+
+use light_client_core::prelude::*;
+use light_client_core::{
+    BlockHeaderInnerRest, CompactLightClientBlock, Header, LightClientBlockView, MerklePath,
+    Protocol, ProtocolConfig, RandomBeacon, SyncOutput, ValidatorStake, VerifiedInnerRest,
+};
+
+#[jolt::provable(
+    stack_size = 8192,
+    memory_size = 16777216,
+    max_input_size = 16384,
+    max_output_size = 16384
+)]
+fn sync_lc(
+    head: Header,
+    epoch_bps: Vec<ValidatorStake>,
+    next_block: LightClientBlockView,
+    config: ProtocolConfig,
+) -> SyncOutput {
+    Protocol::sync_with_config(&head, &epoch_bps, next_block, &config)
+        .unwrap()
+        .into()
+}
+
+#[jolt::provable(
+    stack_size = 8192,
+    memory_size = 16777216,
+    max_input_size = 16384,
+    max_output_size = 16384
+)]
+fn sync_lc_compact(
+    head: Header,
+    epoch_bps: Vec<ValidatorStake>,
+    next_block: CompactLightClientBlock,
+    config: ProtocolConfig,
+) -> SyncOutput {
+    Protocol::sync_with_config(&head, &epoch_bps, next_block, &config)
+        .unwrap()
+        .into()
+}
+
+#[jolt::provable(
+    stack_size = 8192,
+    memory_size = 16777216,
+    max_input_size = 16384,
+    max_output_size = 4096
+)]
+fn verify_inner_rest(
+    head: Header,
+    epoch_bps: Vec<ValidatorStake>,
+    next_block: LightClientBlockView,
+    inner_rest: BlockHeaderInnerRest,
+) -> VerifiedInnerRest {
+    let new_head = Protocol::sync(&head, &epoch_bps, next_block)
+        .unwrap()
+        .new_head;
+    Protocol::ensure_inner_rest_matches(&new_head, &inner_rest).unwrap();
+    VerifiedInnerRest::new(new_head.hash(), &inner_rest)
+}
+
+#[jolt::provable(
+    stack_size = 8192,
+    memory_size = 16777216,
+    max_input_size = 16384,
+    max_output_size = 1024
+)]
+fn random_beacon(
+    head: Header,
+    epoch_bps: Vec<ValidatorStake>,
+    next_block: LightClientBlockView,
+    target: Header,
+    block_proof: MerklePath,
+    inner_rest: BlockHeaderInnerRest,
+) -> RandomBeacon {
+    let new_head = Protocol::sync(&head, &epoch_bps, next_block)
+        .unwrap()
+        .new_head;
+    Protocol::verify_random_value(&new_head, &target, &block_proof, &inner_rest).unwrap()
+}
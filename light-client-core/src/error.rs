@@ -0,0 +1,17 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    BlockAlreadyVerified,
+    BlockNotCurrentOrNextEpoch,
+    SignatureInvalid,
+    NotEnoughApprovedStake,
+    NextBpsInvalid,
+    ValidatorNotSigned,
+    NoBlocksToSync,
+    HeadBlockRootMismatch,
+    InclusionProofInvalid,
+    OutcomeRootProofInvalid,
+    InnerRestHashMismatch,
+    BlockNotAncestral,
+    StakeRotationExceeded,
+    ApprovalsMalformed,
+}
@@ -0,0 +1,23 @@
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
+
+pub use crate::light_client::{Protocol, SyncBlock, SyncOutput, Synced, SyncedOutcome};
+pub use error::Error;
+pub use predicate::{OutcomePredicate, Predicate, PredicateOutput, StatusKind};
+pub use types::{
+    combine_hash, hash, hash_borsh, BasicProof, BlockHeaderInnerRest, CompactLightClientBlock,
+    Hash, Header, LcProof, LightClientBlockView, MerklePath, Network, ProtocolConfig, RandomBeacon,
+    StakeInfo, TrustedState, ValidatorStake, VerifiedInnerRest, VerifiedOutcome,
+};
+
+#[macro_use]
+pub mod profile;
+
+mod error;
+mod light_client;
+mod predicate;
+
+pub mod prelude {
+    pub extern crate alloc;
+    pub use alloc::*;
+    pub use vec::Vec;
+}
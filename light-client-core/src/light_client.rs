@@ -0,0 +1,764 @@
+use crate::error::Error;
+use crate::predicate::{OutcomePredicate, PredicateOutput};
+use crate::prelude::*;
+#[cfg(feature = "cycle-tracking")]
+use crate::profile::*;
+use alloc::boxed::Box;
+use borsh::BorshSerialize;
+use ed25519_dalek::Verifier;
+use types::*;
+
+type Result<T> = core::result::Result<T, Error>;
+
+// TODO: remove
+pub const NUM_BLOCK_PRODUCER_SEATS: usize = 50;
+
+#[derive(Debug)]
+pub struct Synced {
+    pub new_head: Header,
+    pub next_bps: Option<(EpochId, Vec<ValidatorStake>)>,
+    pub participation: Participation,
+    /// How `next_bps` differs from the producers that signed the block.
+    pub bps_diff: Option<BpsDiff>,
+}
+
+/// Public output of a sync step.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SyncOutput {
+    pub new_head: Header,
+    pub participation: Participation,
+    pub bps_diff: Option<BpsDiff>,
+}
+
+impl From<Synced> for SyncOutput {
+    fn from(synced: Synced) -> Self {
+        Self {
+            new_head: synced.new_head,
+            participation: synced.participation,
+            bps_diff: synced.bps_diff,
+        }
+    }
+}
+
+/// Public output of syncing to a new head and proving an outcome against it.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SyncedOutcome {
+    pub state_commitment: Hash,
+    pub head_block_hash: Hash,
+    pub outcome: VerifiedOutcome,
+}
+
+/// A light client block in a form `Protocol::sync` verifies as it is, without
+/// converting it to another first.
+pub trait SyncBlock {
+    type Approvals<'a>: Iterator<Item = Option<ed25519_dalek::Signature>>
+    where
+        Self: 'a;
+
+    fn header(&self) -> HeaderRef<'_>;
+
+    fn next_block_inner_hash(&self) -> &Hash;
+
+    fn next_bps(&self) -> &Option<Vec<ValidatorStakeView>>;
+
+    /// The approval of each seat in order.
+    fn approvals(&self) -> Result<Self::Approvals<'_>>;
+
+    /// The header, and the next block producers if any.
+    fn into_parts(self) -> (Header, Option<Vec<ValidatorStakeView>>);
+}
+
+impl SyncBlock for LightClientBlockView {
+    type Approvals<'a> = core::iter::Map<
+        core::slice::Iter<'a, Option<Box<Signature>>>,
+        fn(&Option<Box<Signature>>) -> Option<ed25519_dalek::Signature>,
+    >;
+
+    fn header(&self) -> HeaderRef<'_> {
+        self.into()
+    }
+
+    fn next_block_inner_hash(&self) -> &Hash {
+        &self.next_block_inner_hash
+    }
+
+    fn next_bps(&self) -> &Option<Vec<ValidatorStakeView>> {
+        &self.next_bps
+    }
+
+    fn approvals(&self) -> Result<Self::Approvals<'_>> {
+        let signature: fn(&Option<Box<Signature>>) -> Option<ed25519_dalek::Signature> =
+            |approval| approval.as_ref().map(|signature| signature.0);
+        Ok(self.approvals_after_next.iter().map(signature))
+    }
+
+    fn into_parts(self) -> (Header, Option<Vec<ValidatorStakeView>>) {
+        let header = Header {
+            prev_block_hash: self.prev_block_hash,
+            inner_rest_hash: self.inner_rest_hash,
+            inner_lite: self.inner_lite,
+        };
+        (header, self.next_bps)
+    }
+}
+
+impl SyncBlock for CompactLightClientBlock {
+    type Approvals<'a> = CompactApprovals<'a>;
+
+    fn header(&self) -> HeaderRef<'_> {
+        self.into()
+    }
+
+    fn next_block_inner_hash(&self) -> &Hash {
+        &self.next_block_inner_hash
+    }
+
+    fn next_bps(&self) -> &Option<Vec<ValidatorStakeView>> {
+        &self.next_bps
+    }
+
+    fn approvals(&self) -> Result<Self::Approvals<'_>> {
+        CompactLightClientBlock::approvals(self).map_err(|_| Error::ApprovalsMalformed)
+    }
+
+    fn into_parts(self) -> (Header, Option<Vec<ValidatorStakeView>>) {
+        let header = Header {
+            prev_block_hash: self.prev_block_hash,
+            inner_rest_hash: self.inner_rest_hash,
+            inner_lite: self.inner_lite,
+        };
+        (header, self.next_bps)
+    }
+}
+
+pub struct Protocol;
+
+impl Protocol {
+    pub fn sync(
+        head: &Header,
+        epoch_bps: &[ValidatorStake],
+        next_block: impl SyncBlock,
+    ) -> Result<Synced> {
+        let inner_lite = next_block.header().inner_lite;
+        // A phase must end before `?` returns, so each evaluates to its `Result`
+        phase!(
+            SYNC_CHECKS,
+            Self::ensure_not_already_verified(head, &inner_lite.height)
+                .and_then(|()| Self::ensure_epoch_is_current_or_next(head, &inner_lite.epoch_id))
+                .and_then(|()| {
+                    Self::ensure_if_next_epoch_contains_next_bps(
+                        head,
+                        &inner_lite.epoch_id,
+                        next_block.next_bps(),
+                    )
+                })
+        )?;
+
+        let approval_message = phase!(
+            SYNC_APPROVAL_MESSAGE,
+            Self::reconstruct_approval_message(&next_block).unwrap()
+        );
+
+        let (StakeInfo { total, approved }, participation) = phase!(
+            SYNC_SIGNATURES,
+            next_block.approvals().map(|approvals| {
+                Self::validate_approvals_of(approvals, epoch_bps, &approval_message)
+            })
+        )?;
+
+        Self::ensure_stake_is_sufficient(&total, &approved)?;
+
+        // Everything else is checked, so the rest of the block can move into the head
+        let (new_head, next_bps) = next_block.into_parts();
+
+        let next_bps: Option<Vec<ValidatorStake>> = phase!(
+            SYNC_NEXT_BPS_HASH,
+            Self::ensure_next_bps_is_valid(&new_head.inner_lite.next_bp_hash, next_bps)
+        )?
+        .map(|next_bps| next_bps.into_iter().map(Into::into).collect());
+
+        Ok(Synced {
+            new_head,
+            bps_diff: phase!(
+                SYNC_BPS_DIFF,
+                next_bps
+                    .as_ref()
+                    .map(|next_bps| BpsDiff::compute(epoch_bps, next_bps))
+            ),
+            next_bps: next_bps.map(|next_bps| (head.inner_lite.next_epoch_id, next_bps)),
+            participation,
+        })
+    }
+
+    /// Sync, then apply the bounds in `config` to the result.
+    pub fn sync_with_config(
+        head: &Header,
+        epoch_bps: &[ValidatorStake],
+        next_block: impl SyncBlock,
+        config: &ProtocolConfig,
+    ) -> Result<Synced> {
+        let synced = Self::sync(head, epoch_bps, next_block)?;
+        if let (Some(diff), Some(max)) = (&synced.bps_diff, config.max_rotated_stake_bps) {
+            Self::ensure_stake_rotation_within(diff, max)?;
+        }
+        Ok(synced)
+    }
+
+    /// Sync through each of `blocks` in order, starting from a trusted state.
+    pub fn sync_many(
+        mut state: TrustedState,
+        blocks: Vec<LightClientBlockView>,
+    ) -> Result<TrustedState> {
+        if blocks.is_empty() {
+            return Err(Error::NoBlocksToSync);
+        }
+
+        for block in blocks {
+            let synced = Self::sync(&state.head, &state.epoch_bps, block)?;
+            state.head = synced.new_head;
+            if let Some((_, next_bps)) = synced.next_bps {
+                state.epoch_bps = next_bps;
+            }
+        }
+
+        Ok(state)
+    }
+
+    /// Sync from `state` to a new head, then verify `proof` against that head,
+    /// so the outcome is proven without trusting a `head_block_root`.
+    pub fn sync_and_include(
+        state: TrustedState,
+        blocks: Vec<LightClientBlockView>,
+        proof: LcProof,
+        num_shards: ShardId,
+    ) -> Result<SyncedOutcome> {
+        let state = Self::sync_many(state, blocks)?;
+
+        if proof.block_merkle_root() != &state.head.inner_lite.block_merkle_root {
+            return Err(Error::HeadBlockRootMismatch);
+        }
+
+        Ok(SyncedOutcome {
+            state_commitment: state.commitment(),
+            head_block_hash: state.head.hash(),
+            outcome: Self::verify_inclusion(proof, num_shards)?,
+        })
+    }
+
+    pub fn inclusion_proof_verify(proof: LcProof) -> Result<bool> {
+        match proof {
+            LcProof::Basic {
+                head_block_root,
+                proof,
+            } => match Self::verify_basic_proof(&head_block_root, &proof) {
+                Ok(_) => Ok(true),
+                Err(Error::InclusionProofInvalid) => Ok(false),
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    /// Verify an inclusion proof for a chain with `num_shards` shards,
+    /// returning the outcome it proves and the shard it came from.
+    pub fn verify_inclusion(proof: LcProof, num_shards: ShardId) -> Result<VerifiedOutcome> {
+        match proof {
+            LcProof::Basic {
+                head_block_root,
+                proof,
+            } => {
+                let block_hash = Self::verify_basic_proof(&head_block_root, &proof)?;
+                let shard_id = phase!(
+                    INCLUSION_SHARD,
+                    Self::derive_shard_id(&proof.outcome_root_proof, num_shards)
+                )?;
+
+                Ok(VerifiedOutcome {
+                    shard_id,
+                    block_hash,
+                    id: proof.outcome_proof.id,
+                    outcome: proof.outcome_proof.outcome,
+                })
+            }
+        }
+    }
+
+    /// Verify each of `proofs` against one `head_block_root`, returning the
+    /// outcome ids with whether their proof holds.
+    pub fn verify_batch(head_block_root: &Hash, proofs: &[BasicProof]) -> Vec<(Hash, bool)> {
+        proofs
+            .iter()
+            .map(|proof| {
+                let verified = Self::verify_basic_proof(head_block_root, proof).is_ok();
+                (proof.outcome_proof.id, verified)
+            })
+            .collect()
+    }
+
+    /// Verify a basic proof against `head_block_root`, returning the hash of the
+    /// block the outcome was included in.
+    fn verify_basic_proof(head_block_root: &Hash, proof: &BasicProof) -> Result<Hash> {
+        let block_hash = phase!(INCLUSION_BLOCK_HASH, proof.block_header_lite.hash());
+        let block_hash_matches = block_hash == proof.outcome_proof.block_hash;
+
+        let outcome_hash = hash_borsh(
+            proof
+                .outcome_proof
+                .outcome
+                .to_hashes(proof.outcome_proof.id),
+        );
+
+        let outcome_verified = phase!(
+            INCLUSION_OUTCOME_ROOT,
+            Self::verify_outcome(
+                &outcome_hash,
+                proof.outcome_proof.proof.iter(),
+                proof.outcome_root_proof.iter(),
+                &proof.block_header_lite.inner_lite.outcome_root,
+            )
+        );
+
+        let block_verified = phase!(
+            INCLUSION_BLOCK_ROOT,
+            Self::verify_block(head_block_root, proof.block_proof.iter(), &block_hash)
+        );
+
+        if block_hash_matches && outcome_verified && block_verified {
+            Ok(block_hash)
+        } else {
+            Err(Error::InclusionProofInvalid)
+        }
+    }
+
+    /// The outcome root is a merkle tree over one chunk outcome root per shard,
+    /// so the shape of `outcome_root_proof` identifies the shard.
+    pub fn derive_shard_id(
+        outcome_root_proof: &[MerklePathItem],
+        num_shards: ShardId,
+    ) -> Result<ShardId> {
+        (0..num_shards)
+            .find(|shard_id| {
+                let directions = path_directions(*shard_id, num_shards);
+                directions.len() == outcome_root_proof.len()
+                    && directions
+                        .iter()
+                        .zip(outcome_root_proof.iter())
+                        .all(|(direction, item)| direction == &item.direction)
+            })
+            .ok_or(Error::OutcomeRootProofInvalid)
+    }
+
+    /// Verify the inclusion proof, then evaluate `predicate` over the proven outcome.
+    pub fn inclusion_proof_verify_with<P: OutcomePredicate>(
+        proof: LcProof,
+        predicate: &P,
+    ) -> Result<PredicateOutput> {
+        let (head_block_root, outcome_id, satisfied) = match &proof {
+            LcProof::Basic {
+                head_block_root,
+                proof,
+            } => (
+                *head_block_root,
+                proof.outcome_proof.id,
+                predicate.evaluate(&proof.outcome_proof.outcome),
+            ),
+        };
+
+        let verified = Self::inclusion_proof_verify(proof)?;
+
+        Ok(PredicateOutput {
+            head_block_root,
+            outcome_id,
+            predicate_hash: predicate.hash(),
+            verified,
+            // An unproven outcome never satisfies a predicate
+            satisfied: verified && satisfied,
+        })
+    }
+
+    pub fn verify_outcome<'a>(
+        outcome_hash: &Hash,
+        outcome_proof: impl Iterator<Item = &'a MerklePathItem>,
+        outcome_root_proof: impl Iterator<Item = &'a MerklePathItem>,
+        expected_outcome_root: &Hash,
+    ) -> bool {
+        let outcome_root = compute_root_from_path(outcome_proof, *outcome_hash);
+        #[cfg(test)]
+        println!("outcome_root: {:?}", hex::encode(outcome_root));
+
+        let leaf = hash_borsh(outcome_root);
+        #[cfg(test)]
+        println!("leaf: {:?}", hex::encode(leaf));
+
+        let outcome_root = compute_root_from_path(outcome_root_proof, leaf);
+        #[cfg(test)]
+        println!("outcome_root: {:?}", hex::encode(outcome_root));
+
+        &outcome_root == expected_outcome_root
+    }
+
+    pub fn verify_block<'a>(
+        block_merkle_root: &Hash,
+        block_proof: impl Iterator<Item = &'a MerklePathItem>,
+        block_hash: &Hash,
+    ) -> bool {
+        verify_hash(*block_merkle_root, block_proof, *block_hash)
+    }
+
+    pub fn reconstruct_approval_message(block_view: &impl SyncBlock) -> Option<Vec<u8>> {
+        let header = block_view.header();
+        let next_block_hash = combine_hash(
+            block_view.next_block_inner_hash(),
+            &phase!(SYNC_HEADER_HASH, header.hash()),
+        );
+
+        let endorsement = ApprovalInner::Endorsement(next_block_hash);
+
+        let approval_message = {
+            let mut temp_vec = Vec::new();
+            BorshSerialize::serialize(&endorsement, &mut temp_vec).ok()?;
+            temp_vec.extend_from_slice(&((header.inner_lite.height + 2).to_le_bytes()[..]));
+            #[cfg(test)]
+            println!("temp_vec len: {:?}", temp_vec.len());
+            temp_vec
+        };
+
+        Option::Some(approval_message)
+    }
+
+    /// Read the random value of `target`, which must be `head` or be proven
+    /// against its block merkle root.
+    pub fn verify_random_value(
+        head: &Header,
+        target: &Header,
+        block_proof: &[MerklePathItem],
+        inner_rest: &BlockHeaderInnerRest,
+    ) -> Result<RandomBeacon> {
+        let block_hash = target.hash();
+        if block_hash != head.hash()
+            && !Self::verify_block(
+                &head.inner_lite.block_merkle_root,
+                block_proof.iter(),
+                &block_hash,
+            )
+        {
+            return Err(Error::BlockNotAncestral);
+        }
+
+        Self::ensure_inner_rest_matches(target, inner_rest)?;
+
+        Ok(RandomBeacon {
+            height: target.inner_lite.height,
+            block_hash,
+            random_value: *inner_rest.random_value(),
+        })
+    }
+
+    pub fn ensure_inner_rest_matches(
+        header: &Header,
+        inner_rest: &BlockHeaderInnerRest,
+    ) -> Result<()> {
+        if inner_rest.hash() != header.inner_rest_hash {
+            Err(Error::InnerRestHashMismatch)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn ensure_not_already_verified(head: &Header, block_height: &BlockHeight) -> Result<()> {
+        if block_height <= &head.inner_lite.height {
+            Err(Error::BlockAlreadyVerified)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn ensure_epoch_is_current_or_next(head: &Header, epoch_id: &Hash) -> Result<()> {
+        if ![head.inner_lite.epoch_id, head.inner_lite.next_epoch_id].contains(epoch_id) {
+            Err(Error::BlockNotCurrentOrNextEpoch)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn ensure_if_next_epoch_contains_next_bps(
+        head: &Header,
+        epoch_id: &Hash,
+        next_bps: &Option<Vec<ValidatorStakeView>>,
+    ) -> Result<()> {
+        if &head.inner_lite.next_epoch_id == epoch_id && next_bps.is_none() {
+            Err(Error::NextBpsInvalid)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn validate_signatures(
+        signatures: &[Option<Box<Signature>>],
+        epoch_bps: &[ValidatorStake],
+        approval_message: &[u8],
+    ) -> StakeInfo {
+        Self::validate_approvals(signatures, epoch_bps, approval_message).0
+    }
+
+    /// Validate the approvals of `epoch_bps`, recording which of them approved
+    /// alongside the stake.
+    pub fn validate_approvals(
+        signatures: &[Option<Box<Signature>>],
+        epoch_bps: &[ValidatorStake],
+        approval_message: &[u8],
+    ) -> (StakeInfo, Participation) {
+        Self::validate_approvals_of(
+            signatures
+                .iter()
+                .map(|approval| approval.as_ref().map(|signature| signature.0)),
+            epoch_bps,
+            approval_message,
+        )
+    }
+
+    /// As `validate_approvals`, over the approval of each seat in order.
+    fn validate_approvals_of(
+        mut approvals: impl Iterator<Item = Option<ed25519_dalek::Signature>>,
+        epoch_bps: &[ValidatorStake],
+        approval_message: &[u8],
+    ) -> (StakeInfo, Participation) {
+        // Only the approvals of the first seats are checked, so later
+        // producers are left out of the participation altogether
+        let seats = &epoch_bps[..epoch_bps.len().min(NUM_BLOCK_PRODUCER_SEATS)];
+        let mut participation = Participation::new(bps_hash(epoch_bps), seats.len());
+        let (mut total_stake, mut approved_stake) = (0, 0);
+
+        for (i, vs) in seats.iter().enumerate() {
+            let Some(sig) = approvals.next() else {
+                participation.missing.push(i as u32);
+                continue;
+            };
+
+            total_stake += vs.stake;
+
+            match Self::verify_approval(approval_message, sig.as_ref(), &vs.public_key) {
+                Ok(_) => {
+                    approved_stake += vs.stake;
+                    participation.approve(i);
+                }
+                Err(Error::ValidatorNotSigned) => participation.missing.push(i as u32),
+                Err(_) => participation.invalid.push(i as u32),
+            }
+        }
+
+        ((total_stake, approved_stake).into(), participation)
+    }
+
+    pub fn validate_signature(
+        msg: &[u8],
+        sig: &Option<Box<Signature>>,
+        pk: &PublicKey,
+    ) -> Result<()> {
+        Self::verify_approval(msg, sig.as_ref().map(|signature| &signature.0), pk)
+    }
+
+    fn verify_approval(
+        msg: &[u8],
+        sig: Option<&ed25519_dalek::Signature>,
+        pk: &PublicKey,
+    ) -> Result<()> {
+        match sig {
+            Some(signature) => match ed25519_dalek::VerifyingKey::from_bytes(pk) {
+                Err(_) => Err(Error::SignatureInvalid),
+                Ok(public_key) => public_key
+                    .verify(msg, signature)
+                    .map_err(|_| Error::SignatureInvalid),
+            },
+            _ => Err(Error::ValidatorNotSigned),
+        }
+    }
+
+    pub fn ensure_stake_rotation_within(diff: &BpsDiff, max_rotated_stake_bps: u32) -> Result<()> {
+        if diff.rotated_stake_bps > max_rotated_stake_bps {
+            Err(Error::StakeRotationExceeded)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn ensure_stake_is_sufficient(total_stake: &u128, approved_stake: &u128) -> Result<()> {
+        let threshold = total_stake / 3 * 2;
+
+        if approved_stake <= &threshold {
+            Err(Error::NotEnoughApprovedStake)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn ensure_next_bps_is_valid(
+        expected_hash: &Hash,
+        next_bps: Option<Vec<ValidatorStakeView>>,
+    ) -> Result<Option<Vec<ValidatorStakeView>>> {
+        if let Some(next_bps) = next_bps {
+            let next_bps_hash = bps_hash(&next_bps);
+
+            if &next_bps_hash == expected_hash {
+                Ok(Some(next_bps))
+            } else {
+                Err(Error::NextBpsInvalid)
+            }
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! cvec {
+	($($x:expr),*) => {
+		{
+			let mut temp_vec = Vec::new();
+			$(
+				temp_vec.extend_from_slice(&$x);
+			)*
+			temp_vec
+		}
+	};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inner_rest(random_value: Hash) -> BlockHeaderInnerRest {
+        BlockHeaderInnerRest::V4(BlockHeaderInnerRestV4 {
+            random_value,
+            ..Default::default()
+        })
+    }
+
+    fn header(height: BlockHeight, inner_rest: &BlockHeaderInnerRest) -> Header {
+        Header {
+            prev_block_hash: [height as u8; 32],
+            inner_rest_hash: inner_rest.hash(),
+            inner_lite: BlockHeaderInnerLiteView {
+                height,
+                epoch_id: [0; 32],
+                next_epoch_id: [1; 32],
+                prev_state_root: [0; 32],
+                outcome_root: [0; 32],
+                timestamp: 0,
+                timestamp_nanosec: 0,
+                next_bp_hash: [0; 32],
+                block_merkle_root: [0; 32],
+            },
+        }
+    }
+
+    #[test]
+    fn test_ensure_inner_rest_matches() {
+        let inner_rest = inner_rest([7; 32]);
+        let header = header(1, &inner_rest);
+        assert_eq!(
+            Protocol::ensure_inner_rest_matches(&header, &inner_rest),
+            Ok(())
+        );
+
+        let mut tampered = inner_rest.clone();
+        let BlockHeaderInnerRest::V4(inner) = &mut tampered else {
+            unreachable!()
+        };
+        inner.total_supply += 1;
+        assert_eq!(
+            Protocol::ensure_inner_rest_matches(&header, &tampered),
+            Err(Error::InnerRestHashMismatch)
+        );
+        // The same fields under another version hash differently
+        let BlockHeaderInnerRest::V4(inner) = inner_rest else {
+            unreachable!()
+        };
+        let as_v3 = BlockHeaderInnerRest::V3(BlockHeaderInnerRestV3 {
+            random_value: inner.random_value,
+            ..Default::default()
+        });
+        assert_eq!(
+            Protocol::ensure_inner_rest_matches(&header, &as_v3),
+            Err(Error::InnerRestHashMismatch)
+        );
+    }
+
+    #[test]
+    fn test_verify_random_value() {
+        let (target_rest, other_rest) = (inner_rest([7; 32]), inner_rest([8; 32]));
+        let (target, other) = (header(1, &target_rest), header(2, &other_rest));
+        let (root, paths) = merklize(&[target.hash(), other.hash()]);
+        let mut head = header(3, &inner_rest([9; 32]));
+        head.inner_lite.block_merkle_root = root;
+
+        let beacon = Protocol::verify_random_value(&head, &target, &paths[0], &target_rest);
+        assert_eq!(
+            beacon,
+            Ok(RandomBeacon {
+                height: 1,
+                block_hash: target.hash(),
+                random_value: [7; 32],
+            })
+        );
+        // The head itself needs no proof
+        let head_rest = inner_rest([9; 32]);
+        assert!(Protocol::verify_random_value(&head, &head, &[], &head_rest).is_ok());
+
+        // Another block's preimage, or a random value swapped into this one's
+        assert_eq!(
+            Protocol::verify_random_value(&head, &target, &paths[0], &other_rest),
+            Err(Error::InnerRestHashMismatch)
+        );
+        assert_eq!(
+            Protocol::verify_random_value(&head, &target, &paths[0], &inner_rest([8; 32])),
+            Err(Error::InnerRestHashMismatch)
+        );
+        assert_eq!(
+            Protocol::verify_random_value(&head, &target, &paths[1], &target_rest),
+            Err(Error::BlockNotAncestral)
+        );
+    }
+
+    #[test]
+    fn test_approvals_past_checked_seats() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let keys: Vec<_> = (0..60u8)
+            .map(|i| SigningKey::from_bytes(&[i; 32]))
+            .collect();
+        let epoch_bps: Vec<_> = keys
+            .iter()
+            .map(|key| ValidatorStake {
+                account_id: "bp.near".into(),
+                public_key: key.verifying_key().to_bytes(),
+                stake: 1,
+            })
+            .collect();
+        let message = b"approval";
+        // The first seat signs, as does one past the checked seats
+        let signatures: Vec<_> = (0..keys.len())
+            .map(|i| (i == 0 || i == 55).then(|| Box::new(Signature(keys[i].sign(message)))))
+            .collect();
+
+        let (stake, participation) = Protocol::validate_approvals(&signatures, &epoch_bps, message);
+
+        assert_eq!(
+            stake,
+            StakeInfo {
+                total: NUM_BLOCK_PRODUCER_SEATS as u128,
+                approved: 1
+            }
+        );
+        assert!(participation.approved(0));
+        assert!(!participation.approved(55));
+        assert_eq!(
+            participation.bitmap.len(),
+            NUM_BLOCK_PRODUCER_SEATS.div_ceil(8)
+        );
+        assert_eq!(
+            participation.missing,
+            (1..NUM_BLOCK_PRODUCER_SEATS as u32).collect::<Vec<_>>()
+        );
+        assert!(participation.invalid.is_empty());
+    }
+}
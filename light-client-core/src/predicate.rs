@@ -0,0 +1,158 @@
+use crate::prelude::*;
+use alloc::boxed::Box;
+use alloc::string::String;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use types::*;
+
+/// A check applied to an execution outcome once its inclusion has been proven.
+pub trait OutcomePredicate {
+    fn evaluate(&self, outcome: &ExecutionOutcomeView) -> bool;
+
+    /// Commitment to the predicate, so a verifier knows which check was applied.
+    fn hash(&self) -> Hash;
+}
+
+/// The kind of an execution status, ignoring its payload.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum StatusKind {
+    Unknown,
+    Failure,
+    SuccessValue,
+    SuccessReceiptId,
+}
+
+impl From<&PartialExecutionStatus> for StatusKind {
+    fn from(status: &PartialExecutionStatus) -> Self {
+        match status {
+            PartialExecutionStatus::Unknown => StatusKind::Unknown,
+            PartialExecutionStatus::Failure => StatusKind::Failure,
+            PartialExecutionStatus::SuccessValue(_) => StatusKind::SuccessValue,
+            PartialExecutionStatus::SuccessReceiptId(_) => StatusKind::SuccessReceiptId,
+        }
+    }
+}
+
+/// A small declarative predicate language over `ExecutionOutcomeView`, so that
+/// one guest binary can serve many applications.
+///
+/// e.g. "executor is bridge.near and status is SuccessValue":
+/// `Predicate::All(vec![Predicate::ExecutorIs("bridge.near".into()), Predicate::StatusIs(StatusKind::SuccessValue)])`
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Predicate {
+    /// Always satisfied.
+    True,
+    /// The account the outcome was executed on.
+    ExecutorIs(AccountId),
+    StatusIs(StatusKind),
+    /// Any log line contains the given substring.
+    LogContains(String),
+    /// The outcome generated the given receipt.
+    ReceiptIdsContain(Hash),
+    Not(Box<Predicate>),
+    All(Vec<Predicate>),
+    Any(Vec<Predicate>),
+}
+
+impl OutcomePredicate for Predicate {
+    fn evaluate(&self, outcome: &ExecutionOutcomeView) -> bool {
+        match self {
+            Predicate::True => true,
+            Predicate::ExecutorIs(executor_id) => &outcome.executor_id == executor_id,
+            Predicate::StatusIs(kind) => &StatusKind::from(&outcome.status) == kind,
+            Predicate::LogContains(needle) => {
+                outcome.logs.iter().any(|log| log.contains(needle.as_str()))
+            }
+            Predicate::ReceiptIdsContain(receipt_id) => outcome.receipt_ids.contains(receipt_id),
+            Predicate::Not(inner) => !inner.evaluate(outcome),
+            Predicate::All(inner) => inner.iter().all(|p| p.evaluate(outcome)),
+            Predicate::Any(inner) => inner.iter().any(|p| p.evaluate(outcome)),
+        }
+    }
+
+    fn hash(&self) -> Hash {
+        hash_borsh(self)
+    }
+}
+
+/// Public output of an inclusion proof checked against a predicate.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PredicateOutput {
+    pub head_block_root: Hash,
+    pub outcome_id: Hash,
+    pub predicate_hash: Hash,
+    pub verified: bool,
+    pub satisfied: bool,
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::Protocol;
+
+    /// A recorded proof, under the root its block proof leads to.
+    fn proof(name: &str) -> LcProof {
+        let json = std::fs::read_to_string(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../fixtures")
+                .join(name),
+        )
+        .unwrap();
+        let proof = BasicProof::from_rpc_json(&json).unwrap();
+        let head_block_root =
+            compute_root_from_path(proof.block_proof.iter(), proof.block_header_lite.hash());
+        (head_block_root, proof).into()
+    }
+
+    fn outcome(proof: &LcProof) -> &ExecutionOutcomeView {
+        match proof {
+            LcProof::Basic { proof, .. } => &proof.outcome_proof.outcome,
+        }
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let (new, old) = (proof("new.json"), proof("old.json"));
+        let receipt_id = outcome(&new).receipt_ids[0];
+        let predicate = Predicate::All(vec![
+            Predicate::ExecutorIs("cdk.topgunbakugo.testnet".into()),
+            Predicate::StatusIs(StatusKind::SuccessValue),
+            Predicate::LogContains("blobs".into()),
+            Predicate::ReceiptIdsContain(receipt_id),
+        ]);
+
+        assert!(predicate.evaluate(outcome(&new)));
+        // Same executor, status and logs, but a different receipt
+        assert!(!predicate.evaluate(outcome(&old)));
+        assert!(Predicate::Not(Box::new(predicate.clone())).evaluate(outcome(&old)));
+        assert!(Predicate::Any(vec![
+            Predicate::StatusIs(StatusKind::Failure),
+            Predicate::LogContains("blobs".into()),
+        ])
+        .evaluate(outcome(&old)));
+        assert!(!Predicate::ExecutorIs("bridge.near".into()).evaluate(outcome(&new)));
+    }
+
+    #[test]
+    fn test_inclusion_proof_verify_with() {
+        let predicate = Predicate::ExecutorIs("cdk.topgunbakugo.testnet".into());
+        let output = Protocol::inclusion_proof_verify_with(proof("new.json"), &predicate).unwrap();
+        assert!(output.verified && output.satisfied);
+        assert_eq!(output.predicate_hash, predicate.hash());
+
+        let predicate = Predicate::ExecutorIs("bridge.near".into());
+        let output = Protocol::inclusion_proof_verify_with(proof("old.json"), &predicate).unwrap();
+        assert!(output.verified && !output.satisfied);
+
+        // A proof of new.json under old.json's root doesn't hold, so not even
+        // `True` is satisfied
+        let LcProof::Basic { proof: new, .. } = proof("new.json");
+        let LcProof::Basic {
+            head_block_root, ..
+        } = proof("old.json");
+        let output =
+            Protocol::inclusion_proof_verify_with((head_block_root, *new).into(), &Predicate::True)
+                .unwrap();
+        assert!(!output.verified && !output.satisfied);
+    }
+}
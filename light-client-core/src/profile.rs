@@ -0,0 +1,50 @@
+//! Cycle-count markers around each phase of `Protocol`, reported by the Jolt
+//! tracer when the `cycle-tracking` feature is enabled.
+
+pub const SYNC_CHECKS: &str = "sync:checks";
+pub const SYNC_HEADER_HASH: &str = "sync:header_hash";
+pub const SYNC_APPROVAL_MESSAGE: &str = "sync:approval_message";
+pub const SYNC_SIGNATURES: &str = "sync:signatures";
+pub const SYNC_NEXT_BPS_HASH: &str = "sync:next_bps_hash";
+pub const SYNC_BPS_DIFF: &str = "sync:bps_diff";
+
+pub const INCLUSION_BLOCK_HASH: &str = "inclusion:block_hash";
+pub const INCLUSION_OUTCOME_ROOT: &str = "inclusion:outcome_root";
+pub const INCLUSION_BLOCK_ROOT: &str = "inclusion:block_root";
+pub const INCLUSION_SHARD: &str = "inclusion:shard";
+
+/// Every phase of `Protocol::sync`, in order.
+pub const SYNC_PHASES: &[&str] = &[
+    SYNC_CHECKS,
+    SYNC_APPROVAL_MESSAGE,
+    SYNC_HEADER_HASH,
+    SYNC_SIGNATURES,
+    SYNC_NEXT_BPS_HASH,
+    SYNC_BPS_DIFF,
+];
+
+/// Phases measured within another phase, so already counted by it.
+pub const NESTED_PHASES: &[&str] = &[SYNC_HEADER_HASH];
+
+/// Every phase of `Protocol::verify_inclusion`, in order.
+pub const INCLUSION_PHASES: &[&str] = &[
+    INCLUSION_BLOCK_HASH,
+    INCLUSION_OUTCOME_ROOT,
+    INCLUSION_BLOCK_ROOT,
+    INCLUSION_SHARD,
+];
+
+/// Evaluate `$body` as the phase `$label`. `$body` mustn't return early, as
+/// with `?`, or the phase is never ended: evaluate to the `Result` and apply
+/// `?` to the phase instead.
+#[macro_export]
+macro_rules! phase {
+    ($label:expr, $body:expr) => {{
+        #[cfg(feature = "cycle-tracking")]
+        jolt::start_cycle_tracking($label);
+        let result = $body;
+        #[cfg(feature = "cycle-tracking")]
+        jolt::end_cycle_tracking($label);
+        result
+    }};
+}
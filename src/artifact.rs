@@ -0,0 +1,523 @@
+//! Proofs persisted with everything needed to verify them offline: the guest
+//! program and the digest of its build, its public inputs and outputs and the
+//! `ProtocolConfig` proven under.
+//!
+//! An artifact is the magic bytes, a little-endian `u32` format version, a
+//! length-prefixed JSON header and the compressed Jolt proof.
+
+use crate::program::{Program, ProgramDigest};
+use crate::verifier::{
+    BatchInput, BeaconInput, InclusionInput, InnerRestInput, PredicateInput, SyncAndIncludeInput,
+    SyncInput,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use light_client_core::{
+    CompactLightClientBlock, Hash, PredicateOutput, ProtocolConfig, RandomBeacon, SyncOutput,
+    SyncedOutcome, VerifiedInnerRest, VerifiedOutcome,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+const MAGIC: &[u8; 4] = b"NLCJ";
+pub const ARTIFACT_VERSION: u32 = 2;
+/// Longest header read, well past any real one, so a corrupt length can't
+/// make the reader allocate gigabytes.
+const MAX_HEADER_LEN: usize = 16 << 20;
+
+/// Public inputs and outputs of a proven run.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum PublicIo {
+    SyncLc {
+        input: SyncInput,
+        output: SyncOutput,
+    },
+    /// Recorded with the block as a view, which the guest took compact.
+    SyncLcCompact {
+        input: SyncInput,
+        output: SyncOutput,
+    },
+    VerifyInnerRest {
+        input: InnerRestInput,
+        output: VerifiedInnerRest,
+    },
+    RandomBeacon {
+        input: BeaconInput,
+        output: RandomBeacon,
+    },
+    Inclusion {
+        input: InclusionInput,
+        output: VerifiedOutcome,
+    },
+    InclusionPredicate {
+        input: PredicateInput,
+        output: PredicateOutput,
+    },
+    SyncAndInclude {
+        input: SyncAndIncludeInput,
+        output: SyncedOutcome,
+    },
+    InclusionBatch {
+        input: BatchInput,
+        output: Vec<(Hash, bool)>,
+    },
+}
+
+impl PublicIo {
+    pub fn program(&self) -> Program {
+        match self {
+            Self::SyncLc { .. } => Program::SyncLc,
+            Self::SyncLcCompact { .. } => Program::SyncLcCompact,
+            Self::VerifyInnerRest { .. } => Program::VerifyInnerRest,
+            Self::RandomBeacon { .. } => Program::RandomBeacon,
+            Self::Inclusion { .. } => Program::Inclusion,
+            Self::InclusionPredicate { .. } => Program::InclusionPredicate,
+            Self::SyncAndInclude { .. } => Program::SyncAndInclude,
+            Self::InclusionBatch { .. } => Program::InclusionBatch,
+        }
+    }
+
+    pub fn output_json(&self) -> serde_json::Value {
+        match self {
+            Self::SyncLc { output, .. } | Self::SyncLcCompact { output, .. } => {
+                serde_json::json!(output)
+            }
+            Self::VerifyInnerRest { output, .. } => serde_json::json!(output),
+            Self::RandomBeacon { output, .. } => serde_json::json!(output),
+            Self::Inclusion { output, .. } => serde_json::json!(output),
+            Self::InclusionPredicate { output, .. } => serde_json::json!(output),
+            Self::SyncAndInclude { output, .. } => serde_json::json!(output),
+            Self::InclusionBatch { output, .. } => serde_json::json!(output),
+        }
+    }
+
+    /// The inputs as the guest reads them: each argument postcard-encoded in
+    /// turn, ending with the config for the guests that take one.
+    fn guest_inputs(&self, config: &ProtocolConfig) -> Vec<u8> {
+        let mut inputs = Vec::new();
+        let mut push = |bytes: postcard::Result<Vec<u8>>| inputs.extend(bytes.unwrap());
+        match self {
+            Self::SyncLc { input, .. } => {
+                push(postcard::to_stdvec(&input.head));
+                push(postcard::to_stdvec(&input.epoch_bps));
+                push(postcard::to_stdvec(&input.next_block));
+            }
+            Self::SyncLcCompact { input, .. } => {
+                push(postcard::to_stdvec(&input.head));
+                push(postcard::to_stdvec(&input.epoch_bps));
+                push(postcard::to_stdvec(&CompactLightClientBlock::from(
+                    input.next_block.clone(),
+                )));
+            }
+            Self::VerifyInnerRest { input, .. } => {
+                push(postcard::to_stdvec(&input.sync.head));
+                push(postcard::to_stdvec(&input.sync.epoch_bps));
+                push(postcard::to_stdvec(&input.sync.next_block));
+                push(postcard::to_stdvec(&input.inner_rest));
+                return inputs;
+            }
+            Self::RandomBeacon { input, .. } => {
+                push(postcard::to_stdvec(&input.sync.head));
+                push(postcard::to_stdvec(&input.sync.epoch_bps));
+                push(postcard::to_stdvec(&input.sync.next_block));
+                push(postcard::to_stdvec(&input.target));
+                push(postcard::to_stdvec(&input.block_proof));
+                push(postcard::to_stdvec(&input.inner_rest));
+                return inputs;
+            }
+            Self::Inclusion { input, .. } => push(postcard::to_stdvec(&input.proof)),
+            Self::InclusionPredicate { input, .. } => {
+                push(postcard::to_stdvec(&input.proof));
+                push(postcard::to_stdvec(&input.predicate));
+                return inputs;
+            }
+            Self::SyncAndInclude { input, .. } => {
+                push(postcard::to_stdvec(&input.state));
+                push(postcard::to_stdvec(&input.blocks));
+                push(postcard::to_stdvec(&input.proof));
+            }
+            Self::InclusionBatch { input, .. } => {
+                push(postcard::to_stdvec(&input.head_block_root));
+                push(postcard::to_stdvec(&input.proofs));
+                return inputs;
+            }
+        }
+        push(postcard::to_stdvec(config));
+        inputs
+    }
+
+    fn output_matches(&self, outputs: &[u8]) -> bool {
+        fn matches<T: DeserializeOwned + PartialEq>(outputs: &[u8], output: &T) -> bool {
+            postcard::from_bytes::<T>(outputs).is_ok_and(|proven| &proven == output)
+        }
+
+        match self {
+            Self::SyncLc { output, .. } | Self::SyncLcCompact { output, .. } => {
+                matches(outputs, output)
+            }
+            Self::VerifyInnerRest { output, .. } => matches(outputs, output),
+            Self::RandomBeacon { output, .. } => matches(outputs, output),
+            Self::Inclusion { output, .. } => matches(outputs, output),
+            Self::InclusionPredicate { output, .. } => matches(outputs, output),
+            Self::SyncAndInclude { output, .. } => matches(outputs, output),
+            Self::InclusionBatch { output, .. } => matches(outputs, output),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ArtifactHeader {
+    program: Program,
+    program_digest: ProgramDigest,
+    config: ProtocolConfig,
+    io: PublicIo,
+}
+
+#[derive(Debug)]
+pub enum ArtifactError {
+    Io(io::Error),
+    Format(String),
+    UnsupportedVersion(u32),
+    /// The proof is of a different build of the guest than this host's.
+    ProgramMismatch {
+        expected: ProgramDigest,
+        found: ProgramDigest,
+    },
+    /// The inputs or outputs recorded don't match the ones proven.
+    PublicIoMismatch,
+    ProofInvalid,
+}
+
+impl fmt::Display for ArtifactError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Format(e) => write!(f, "malformed artifact: {}", e),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported artifact version {}", version)
+            }
+            Self::ProgramMismatch { expected, found } => write!(
+                f,
+                "proof is of guest build {}, expected {}",
+                found, expected
+            ),
+            Self::PublicIoMismatch => write!(f, "public inputs or outputs don't match the proof"),
+            Self::ProofInvalid => write!(f, "proof is invalid"),
+        }
+    }
+}
+
+impl From<io::Error> for ArtifactError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+pub struct ProofArtifact {
+    pub program_digest: ProgramDigest,
+    pub config: ProtocolConfig,
+    pub io: PublicIo,
+    pub proof: jolt::RV32IHyraxProof,
+}
+
+impl ProofArtifact {
+    pub fn program(&self) -> Program {
+        self.io.program()
+    }
+
+    pub fn write_to(&self, mut writer: impl Write) -> Result<(), ArtifactError> {
+        let header = serde_json::to_vec(&ArtifactHeader {
+            program: self.program(),
+            program_digest: self.program_digest,
+            config: self.config.clone(),
+            io: self.io.clone(),
+        })
+        .map_err(|e| ArtifactError::Format(e.to_string()))?;
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&ARTIFACT_VERSION.to_le_bytes())?;
+        writer.write_all(&(header.len() as u32).to_le_bytes())?;
+        writer.write_all(&header)?;
+        self.proof
+            .serialize_compressed(&mut writer)
+            .map_err(|e| ArtifactError::Format(e.to_string()))
+    }
+
+    pub fn read_from(mut reader: impl Read) -> Result<Self, ArtifactError> {
+        let mut word = [0; 4];
+        reader.read_exact(&mut word)?;
+        if &word != MAGIC {
+            return Err(ArtifactError::Format("not a proof artifact".into()));
+        }
+        reader.read_exact(&mut word)?;
+        let version = u32::from_le_bytes(word);
+        if version != ARTIFACT_VERSION {
+            return Err(ArtifactError::UnsupportedVersion(version));
+        }
+
+        reader.read_exact(&mut word)?;
+        let header_len = u32::from_le_bytes(word) as usize;
+        if header_len > MAX_HEADER_LEN {
+            return Err(ArtifactError::Format(format!(
+                "header of {} bytes",
+                header_len
+            )));
+        }
+        let mut header = vec![0; header_len];
+        reader.read_exact(&mut header)?;
+        let header: ArtifactHeader =
+            serde_json::from_slice(&header).map_err(|e| ArtifactError::Format(e.to_string()))?;
+        if header.program != header.io.program() {
+            return Err(ArtifactError::Format(
+                "public io is not of the artifact's program".into(),
+            ));
+        }
+
+        let proof = jolt::RV32IHyraxProof::deserialize_compressed(reader)
+            .map_err(|e| ArtifactError::Format(e.to_string()))?;
+        Ok(Self {
+            program_digest: header.program_digest,
+            config: header.config,
+            io: header.io,
+            proof,
+        })
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), ArtifactError> {
+        let mut writer = io::BufWriter::new(fs::File::create(path)?);
+        self.write_to(&mut writer)?;
+        Ok(writer.flush()?)
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, ArtifactError> {
+        Self::read_from(io::BufReader::new(fs::File::open(path)?))
+    }
+
+    /// Check the proof is of this host's build of its program and that the
+    /// recorded inputs and outputs are the ones proven, then verify the proof
+    /// against the program's preprocessing.
+    pub fn verify(&self) -> Result<(), ArtifactError> {
+        let expected = self.program().digest();
+        if self.program_digest != expected {
+            return Err(ArtifactError::ProgramMismatch {
+                expected,
+                found: self.program_digest,
+            });
+        }
+
+        let program_io = &self.proof.proof.program_io;
+        if program_io.inputs != self.io.guest_inputs(&self.config)
+            || !self.io.output_matches(&program_io.outputs)
+        {
+            return Err(ArtifactError::PublicIoMismatch);
+        }
+
+        let valid = match self.program() {
+            Program::SyncLc => {
+                let (_, verify) = guest_sync::build_sync_lc();
+                verify(self.proof_copy()?)
+            }
+            Program::SyncLcCompact => {
+                let (_, verify) = guest_sync::build_sync_lc_compact();
+                verify(self.proof_copy()?)
+            }
+            Program::VerifyInnerRest => {
+                let (_, verify) = guest_sync::build_verify_inner_rest();
+                verify(self.proof_copy()?)
+            }
+            Program::RandomBeacon => {
+                let (_, verify) = guest_sync::build_random_beacon();
+                verify(self.proof_copy()?)
+            }
+            Program::Inclusion => {
+                let (_, verify) = guest_inclusion::build_inclusion();
+                verify(self.proof_copy()?)
+            }
+            Program::InclusionPredicate => {
+                let (_, verify) = guest_inclusion::build_inclusion_predicate();
+                verify(self.proof_copy()?)
+            }
+            Program::SyncAndInclude => {
+                let (_, verify) = guest_inclusion::build_sync_and_include();
+                verify(self.proof_copy()?)
+            }
+            Program::InclusionBatch => {
+                let (_, verify) = guest_batch::build_inclusion_batch();
+                verify(self.proof_copy()?)
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(ArtifactError::ProofInvalid)
+        }
+    }
+
+    /// The Jolt verifier takes the proof by value and it isn't `Clone`, so
+    /// verifying copies it through its encoding.
+    fn proof_copy(&self) -> Result<jolt::RV32IHyraxProof, ArtifactError> {
+        let mut bytes = Vec::new();
+        self.proof
+            .serialize_compressed(&mut bytes)
+            .map_err(|e| ArtifactError::Format(e.to_string()))?;
+        jolt::RV32IHyraxProof::deserialize_compressed(&bytes[..])
+            .map_err(|e| ArtifactError::Format(e.to_string()))
+    }
+}
+
+/// Prove `sync_lc` over `input`, keeping the proof.
+pub fn prove_sync(input: SyncInput, config: ProtocolConfig) -> ProofArtifact {
+    let (prove, _) = guest_sync::build_sync_lc();
+    let (output, proof) = prove(
+        input.head.clone(),
+        input.epoch_bps.clone(),
+        input.next_block.clone(),
+        config.clone(),
+    );
+    ProofArtifact {
+        program_digest: Program::SyncLc.digest(),
+        config,
+        io: PublicIo::SyncLc { input, output },
+        proof,
+    }
+}
+
+/// Prove `sync_lc_compact` over `input`, keeping the proof.
+pub fn prove_sync_compact(input: SyncInput, config: ProtocolConfig) -> ProofArtifact {
+    let (prove, _) = guest_sync::build_sync_lc_compact();
+    let (output, proof) = prove(
+        input.head.clone(),
+        input.epoch_bps.clone(),
+        CompactLightClientBlock::from(input.next_block.clone()),
+        config.clone(),
+    );
+    ProofArtifact {
+        program_digest: Program::SyncLcCompact.digest(),
+        config,
+        io: PublicIo::SyncLcCompact { input, output },
+        proof,
+    }
+}
+
+/// Prove `verify_inner_rest` over `input`, keeping the proof. The guest syncs
+/// under the default config, so `config` is recorded but not proven under.
+pub fn prove_inner_rest(input: InnerRestInput, config: ProtocolConfig) -> ProofArtifact {
+    let (prove, _) = guest_sync::build_verify_inner_rest();
+    let (output, proof) = prove(
+        input.sync.head.clone(),
+        input.sync.epoch_bps.clone(),
+        input.sync.next_block.clone(),
+        input.inner_rest.clone(),
+    );
+    ProofArtifact {
+        program_digest: Program::VerifyInnerRest.digest(),
+        config,
+        io: PublicIo::VerifyInnerRest { input, output },
+        proof,
+    }
+}
+
+/// Prove `random_beacon` over `input`, keeping the proof. As with
+/// `prove_inner_rest`, `config` is recorded but not proven under.
+pub fn prove_random_beacon(input: BeaconInput, config: ProtocolConfig) -> ProofArtifact {
+    let (prove, _) = guest_sync::build_random_beacon();
+    let (output, proof) = prove(
+        input.sync.head.clone(),
+        input.sync.epoch_bps.clone(),
+        input.sync.next_block.clone(),
+        input.target.clone(),
+        input.block_proof.clone(),
+        input.inner_rest.clone(),
+    );
+    ProofArtifact {
+        program_digest: Program::RandomBeacon.digest(),
+        config,
+        io: PublicIo::RandomBeacon { input, output },
+        proof,
+    }
+}
+
+/// Prove `inclusion` over `input`, keeping the proof.
+pub fn prove_inclusion(input: InclusionInput, config: ProtocolConfig) -> ProofArtifact {
+    let (prove, _) = guest_inclusion::build_inclusion();
+    let (output, proof) = prove(input.proof.clone(), config.clone());
+    ProofArtifact {
+        program_digest: Program::Inclusion.digest(),
+        config,
+        io: PublicIo::Inclusion { input, output },
+        proof,
+    }
+}
+
+/// Prove `inclusion_predicate` over `input`, keeping the proof. The guest
+/// doesn't derive the shard, so `config` is recorded but not proven under.
+pub fn prove_predicate(input: PredicateInput, config: ProtocolConfig) -> ProofArtifact {
+    let (prove, _) = guest_inclusion::build_inclusion_predicate();
+    let (output, proof) = prove(input.proof.clone(), input.predicate.clone());
+    ProofArtifact {
+        program_digest: Program::InclusionPredicate.digest(),
+        config,
+        io: PublicIo::InclusionPredicate { input, output },
+        proof,
+    }
+}
+
+/// Prove `sync_and_include` over `input`, keeping the proof.
+pub fn prove_sync_and_include(input: SyncAndIncludeInput, config: ProtocolConfig) -> ProofArtifact {
+    let (prove, _) = guest_inclusion::build_sync_and_include();
+    let (output, proof) = prove(
+        input.state.clone(),
+        input.blocks.clone(),
+        input.proof.clone(),
+        config.clone(),
+    );
+    ProofArtifact {
+        program_digest: Program::SyncAndInclude.digest(),
+        config,
+        io: PublicIo::SyncAndInclude { input, output },
+        proof,
+    }
+}
+
+/// Prove `inclusion_batch` over `input`, keeping the proof. The guest checks
+/// proofs alone, so `config` is recorded but not proven under.
+pub fn prove_batch(input: BatchInput, config: ProtocolConfig) -> ProofArtifact {
+    let (prove, _) = guest_batch::build_inclusion_batch();
+    let (output, proof) = prove(input.head_block_root, input.proofs.clone());
+    ProofArtifact {
+        program_digest: Program::InclusionBatch.digest(),
+        config,
+        io: PublicIo::InclusionBatch { input, output },
+        proof,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_rejects_foreign_files() {
+        let err = ProofArtifact::read_from(&b"{\"not\": \"an artifact\"}"[..]).err();
+        assert!(matches!(err, Some(ArtifactError::Format(_))));
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&(ARTIFACT_VERSION + 1).to_le_bytes());
+        let err = ProofArtifact::read_from(&bytes[..]).err();
+        assert!(matches!(
+            err,
+            Some(ArtifactError::UnsupportedVersion(v)) if v == ARTIFACT_VERSION + 1
+        ));
+
+        // A header length past the cap is rejected before it's allocated
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&ARTIFACT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        let err = ProofArtifact::read_from(&bytes[..]).err();
+        assert!(matches!(err, Some(ArtifactError::Format(_))));
+    }
+}
@@ -0,0 +1,93 @@
+//! Serve recorded fixtures or a synthetic chain as a NEAR JSON-RPC endpoint.
+
+use chain_generator::{Chain, ChainConfig, StakeDistribution};
+use clap::{Parser, Subcommand};
+use near_lc_jolt::rpc::FixtureRpc;
+use near_lc_jolt::stand_in::{ChainRpc, StandIn};
+use serde_json::json;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "stand-in-rpc",
+    about = "Stand in for a NEAR node's light client RPC"
+)]
+struct Args {
+    #[arg(long, default_value = "127.0.0.1:3030")]
+    listen: String,
+
+    #[command(subcommand)]
+    source: Source,
+}
+
+#[derive(Subcommand, Debug)]
+enum Source {
+    /// Serve the recorded responses in a directory, such as `fixtures/`.
+    Fixtures { dir: PathBuf },
+    /// Serve a synthetic chain.
+    Generate {
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        #[arg(long, default_value_t = 4)]
+        validators: usize,
+        #[arg(long, default_value_t = 3)]
+        epochs: usize,
+        #[arg(long, default_value_t = 5)]
+        epoch_length: u64,
+        /// Validators replaced at each epoch boundary.
+        #[arg(long, default_value_t = 0)]
+        rotation: usize,
+    },
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let (stand_in, genesis) = match args.source {
+        Source::Fixtures { dir } => match FixtureRpc::load(&dir) {
+            Ok(rpc) => (StandIn::spawn(rpc, &args.listen), None),
+            Err(e) => {
+                println!(
+                    "{}",
+                    json!({ "error": format!("{}: {}", dir.display(), e) })
+                );
+                return ExitCode::FAILURE;
+            }
+        },
+        Source::Generate {
+            seed,
+            validators,
+            epochs,
+            epoch_length,
+            rotation,
+        } => {
+            let chain = Chain::generate(ChainConfig {
+                seed,
+                num_validators: validators,
+                stake: StakeDistribution::Uniform(1_000_000),
+                epochs,
+                epoch_length,
+                rotation,
+                offline: Vec::new(),
+            });
+            let genesis = near_primitives::hash::CryptoHash(chain.block_hash(0));
+            (
+                StandIn::spawn(ChainRpc::new(chain), &args.listen),
+                Some(genesis),
+            )
+        }
+    };
+
+    match stand_in {
+        Ok(stand_in) => {
+            println!("{}", json!({ "url": stand_in.url(), "genesis": genesis }));
+            stand_in.wait();
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            println!("{}", json!({ "error": e.to_string() }));
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,576 @@
+//! Command-line interface of the host binary. Every command reports a JSON
+//! value on stdout, and failures exit non-zero with an `error` report.
+
+use crate::artifact::{self, ProofArtifact};
+use crate::follower::{Event, Follower, FollowerConfig};
+use crate::profile::{self, PhaseReport};
+use crate::prover_service::{ProverRunner, ProverService};
+use crate::recorder;
+use crate::rpc::{HttpRpc, ProofId};
+use crate::store::FileStore;
+use crate::verifier::{input_size, InclusionInput, SyncInput, Verifier};
+use clap::{Parser, Subcommand};
+use light_client_core::{
+    BasicProof, CompactLightClientBlock, Hash, LightClientBlockView, Network, ProtocolConfig,
+    ShardId, TrustedState,
+};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "near-lc-jolt",
+    about = "Prove NEAR light client syncs and inclusions"
+)]
+pub struct Cli {
+    /// Network whose parameters to prove under: mainnet, testnet or localnet.
+    #[arg(long, global = true, env = "NEAR_NETWORK", default_value = "testnet")]
+    pub network: Network,
+
+    /// Checkpoint to start from instead of the network's, as a recorded light
+    /// client block. The network then has no public rpc, so pass `--rpc`.
+    #[arg(long, global = true)]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Number of shards of the checkpoint's chain, by default the network's.
+    #[arg(long, global = true, requires = "checkpoint")]
+    pub num_shards: Option<ShardId>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Sync a trusted state to a light client block.
+    Sync {
+        /// Trusted state, as a checkpoint fixture or `TrustedState` JSON.
+        #[arg(long)]
+        state: PathBuf,
+        /// Light client block, as a fixture or an RPC `LightClientBlockView`.
+        #[arg(long)]
+        block: PathBuf,
+        /// Check the transition natively without proving it.
+        #[arg(long)]
+        dry_run: bool,
+        /// Where to write the proof artifact.
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Prove an execution outcome is included under a block merkle root.
+    ProveInclusion {
+        /// Proof from `EXPERIMENTAL_light_client_proof`.
+        #[arg(long)]
+        proof: PathBuf,
+        /// Block merkle root of the light client head, base58 or hex.
+        #[arg(long, value_parser = parse_hash)]
+        head_root: Hash,
+        #[arg(long)]
+        dry_run: bool,
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Verify a proof artifact written by `sync` or `prove-inclusion`.
+    Verify { proof: PathBuf },
+    /// Summarize a recorded fixture.
+    Inspect { fixture: PathBuf },
+    /// Break down where `sync_lc` spends its cycles syncing to a block.
+    Profile {
+        #[arg(long)]
+        state: PathBuf,
+        #[arg(long)]
+        block: PathBuf,
+    },
+    /// Record consecutive light client blocks and inclusion proofs from an RPC
+    /// as fixtures, with a manifest.
+    Record {
+        /// RPC endpoint, by default the network's public one.
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Block hash to record from, by default the network's checkpoint.
+        #[arg(long, value_parser = parse_hash)]
+        from: Option<Hash>,
+        /// Number of light client blocks, one per epoch.
+        #[arg(long, default_value_t = 3)]
+        epochs: usize,
+        /// Outcomes to record proofs of, as a JSON list like `fixtures/ids.json`.
+        #[arg(long)]
+        ids: Option<PathBuf>,
+        /// Directory to record into, which must not exist yet or be empty.
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Keep syncing from the stored head until interrupted, proving and
+    /// storing each transition.
+    Follow {
+        /// RPC endpoint, by default the network's public one.
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Directory of the header store, started from the network's
+        /// checkpoint if empty.
+        #[arg(long)]
+        store: PathBuf,
+        /// Seconds to wait for a new block once synced.
+        #[arg(long, default_value_t = 60)]
+        poll_interval: u64,
+        /// Most seconds to back off for after failures.
+        #[arg(long, default_value_t = 600)]
+        max_backoff: u64,
+        /// Check transitions without proving or storing them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Serve proof jobs over HTTP in the hosted prover's request schema,
+    /// proving syncs from heads in a header store.
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:3040")]
+        listen: String,
+        /// RPC endpoint, by default the network's public one.
+        #[arg(long)]
+        rpc: Option<String>,
+        /// Directory of the header store the trusted heads are looked up in.
+        #[arg(long)]
+        store: PathBuf,
+    },
+}
+
+impl Cli {
+    /// The network to run on: the one named, or a custom one from
+    /// `--checkpoint` under its parameters.
+    pub fn network(&self) -> Result<Network, Value> {
+        let Some(path) = &self.checkpoint else {
+            return Ok(self.network.clone());
+        };
+        let mut config = self.network.config();
+        if let Some(num_shards) = self.num_shards {
+            config.num_shards = num_shards;
+        }
+        Network::custom_from_file(path, config)
+            .map_err(|e| error(format!("{}: {}", path.display(), e)))
+    }
+}
+
+type Report = Result<Value, Value>;
+
+fn error(message: impl ToString) -> Value {
+    json!({ "error": message.to_string() })
+}
+
+fn base58(hash: &Hash) -> String {
+    near_primitives::hash::CryptoHash(*hash).to_string()
+}
+
+fn parse_hash(s: &str) -> Result<Hash, String> {
+    if let Ok(hash) = s.parse::<near_primitives::hash::CryptoHash>() {
+        return Ok(hash.0);
+    }
+    hex::decode(s.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| format!("not a base58 or hex hash: {}", s))
+}
+
+fn read(path: &Path) -> Result<String, Value> {
+    std::fs::read_to_string(path).map_err(|e| error(format!("{}: {}", path.display(), e)))
+}
+
+pub fn read_state(path: &Path) -> Result<TrustedState, Value> {
+    let json = read(path)?;
+    TrustedState::from_checkpoint_json(&json)
+        .or_else(|_| serde_json::from_str(&json))
+        .map_err(|e| error(format!("{}: not a trusted state: {}", path.display(), e)))
+}
+
+pub fn read_block(path: &Path) -> Result<LightClientBlockView, Value> {
+    let mut value: Value = serde_json::from_str(&read(path)?).map_err(error)?;
+    // Fixtures wrap the block in a `body`
+    if let Some(body) = value.get_mut("body") {
+        value = body.take();
+    }
+    serde_json::from_value::<near_primitives::views::LightClientBlockView>(value)
+        .map(Into::into)
+        .map_err(|e| {
+            error(format!(
+                "{}: not a light client block: {}",
+                path.display(),
+                e
+            ))
+        })
+}
+
+pub fn read_proof(path: &Path) -> Result<BasicProof, Value> {
+    let json = read(path)?;
+    BasicProof::from_rpc_json(&json)
+        .or_else(|_| serde_json::from_str(&json))
+        .map_err(|e| error(format!("{}: not an inclusion proof: {}", path.display(), e)))
+}
+
+pub fn run(command: Command, network: &Network) -> Report {
+    let config = network.config();
+    match command {
+        Command::Sync {
+            state,
+            block,
+            dry_run,
+            out,
+        } => sync(
+            read_state(&state)?,
+            read_block(&block)?,
+            config,
+            dry_run,
+            out,
+        ),
+        Command::ProveInclusion {
+            proof,
+            head_root,
+            dry_run,
+            out,
+        } => prove_inclusion(read_proof(&proof)?, head_root, config, dry_run, out),
+        Command::Verify { proof } => verify(&proof),
+        Command::Inspect { fixture } => inspect(&fixture),
+        Command::Profile { state, block } => {
+            let TrustedState { head, epoch_bps } = read_state(&state)?;
+            let input = SyncInput {
+                head,
+                epoch_bps,
+                next_block: read_block(&block)?,
+            };
+            let compact = CompactLightClientBlock::from(input.next_block.clone());
+            Ok(json!({
+                "input_bytes": {
+                    "next_block": input_size(&input.next_block),
+                    "compact": input_size(&compact),
+                },
+                "sync_lc": phase_report(profile::sync_report(input.clone(), config.clone())),
+                "sync_lc_compact": phase_report(profile::compact_sync_report(input, config)),
+            }))
+        }
+        Command::Record {
+            rpc,
+            from,
+            epochs,
+            ids,
+            out,
+        } => {
+            let rpc = http_rpc(rpc, network)?;
+            let from = match from {
+                Some(from) => from,
+                None => network
+                    .checkpoint()
+                    .ok_or_else(|| error("network has no checkpoint, pass --from"))?
+                    .head
+                    .hash(),
+            };
+            let ids: Vec<ProofId> = match ids {
+                Some(path) => serde_json::from_str(&read(&path)?)
+                    .map_err(|e| error(format!("{}: {}", path.display(), e)))?,
+                None => Vec::new(),
+            };
+            let manifest = recorder::record(&rpc, &Verifier::new(config), from, epochs, &ids, &out)
+                .map_err(error)?;
+            Ok(json!({ "out": out, "manifest": manifest }))
+        }
+        Command::Follow {
+            rpc,
+            store,
+            poll_interval,
+            max_backoff,
+            dry_run,
+        } => {
+            let rpc = http_rpc(rpc, network)?;
+            let store = FileStore::open(&store).map_err(error)?;
+            let config = FollowerConfig {
+                poll_interval: Duration::from_secs(poll_interval),
+                max_backoff: Duration::from_secs(max_backoff),
+                dry_run,
+                ..Default::default()
+            };
+            let mut follower = Follower::resume(
+                rpc,
+                store,
+                Verifier::new(network.config()),
+                config,
+                network.checkpoint(),
+            )
+            .map_err(error)?;
+
+            let shutdown = Arc::new(AtomicBool::new(false));
+            ctrlc::set_handler({
+                let shutdown = shutdown.clone();
+                move || shutdown.store(true, Ordering::SeqCst)
+            })
+            .map_err(error)?;
+            follower.run(&shutdown, |event| println!("{}", event_report(event)));
+            Ok(json!({ "stopped": true, "head": base58(&follower.state().head.hash()) }))
+        }
+        Command::Serve { listen, rpc, store } => {
+            let runner = ProverRunner::new(
+                http_rpc(rpc, network)?,
+                FileStore::open(&store).map_err(error)?,
+                Verifier::new(config),
+            );
+            let service = ProverService::spawn(runner, &listen).map_err(error)?;
+            println!("{}", json!({ "url": service.url() }));
+            service.wait();
+            Ok(json!({ "stopped": true }))
+        }
+    }
+}
+
+fn http_rpc(url: Option<String>, network: &Network) -> Result<HttpRpc, Value> {
+    match url {
+        Some(url) => Ok(HttpRpc::new(url)),
+        None => HttpRpc::for_network(network)
+            .ok_or_else(|| error("network has no public rpc, pass --rpc")),
+    }
+}
+
+fn event_report(event: Event) -> Value {
+    match event {
+        Event::Synced(output) => json!({
+            "event": "synced",
+            "new_head": base58(&output.new_head.hash()),
+            "height": output.new_head.inner_lite.height,
+            "participation": output.participation,
+            "bps_diff": output.bps_diff,
+        }),
+        Event::UpToDate => json!({ "event": "up_to_date" }),
+        Event::Failed { error, retry_in } => json!({
+            "event": "failed",
+            "error": error.to_string(),
+            "retry_in_secs": retry_in.as_secs_f64(),
+        }),
+    }
+}
+
+fn phase_report(report: PhaseReport) -> Value {
+    json!({
+        "total_cycles": report.total_cycles,
+        "untracked_cycles": report.untracked_cycles(),
+        "phases": report
+            .phases
+            .iter()
+            .map(|(label, cycles)| (label.to_string(), json!(cycles)))
+            .collect::<serde_json::Map<_, _>>(),
+    })
+}
+
+fn sync(
+    state: TrustedState,
+    next_block: LightClientBlockView,
+    config: ProtocolConfig,
+    dry_run: bool,
+    out: Option<PathBuf>,
+) -> Report {
+    let new_head = next_block.header().hash();
+    let TrustedState { head, epoch_bps } = state;
+    let input = SyncInput {
+        head,
+        epoch_bps,
+        next_block,
+    };
+    let output = Verifier::new(config.clone())
+        .dry_run(input.clone())
+        .map_err(|e| error(format!("{:?}", e)))?;
+    if dry_run {
+        return Ok(json!({ "new_head": base58(&new_head), "output": output }));
+    }
+
+    let report = prove(artifact::prove_sync(input, config), out)?;
+    Ok(json!({ "new_head": base58(&new_head), "output": output, "proof": report }))
+}
+
+fn prove_inclusion(
+    proof: BasicProof,
+    head_block_root: Hash,
+    config: ProtocolConfig,
+    dry_run: bool,
+    out: Option<PathBuf>,
+) -> Report {
+    let input = InclusionInput {
+        proof: (head_block_root, proof).into(),
+    };
+    let output = Verifier::new(config.clone())
+        .dry_run(input.clone())
+        .map_err(|e| error(format!("{:?}", e)))?;
+    if dry_run {
+        return Ok(json!({ "output": output }));
+    }
+
+    let report = prove(artifact::prove_inclusion(input, config), out)?;
+    Ok(json!({ "output": output, "proof": report }))
+}
+
+/// Verify a freshly proven artifact, then write it to `out` if given, so an
+/// invalid proof is never left there.
+fn prove(artifact: ProofArtifact, out: Option<PathBuf>) -> Result<Value, Value> {
+    artifact.verify().map_err(error)?;
+    if let Some(out) = &out {
+        artifact.write(out).map_err(error)?;
+    }
+    Ok(json!({
+        "valid": true,
+        "program_digest": artifact.program_digest,
+        "path": out,
+    }))
+}
+
+fn verify(path: &Path) -> Report {
+    let artifact = ProofArtifact::read(path).map_err(error)?;
+    let program = artifact.program();
+    let program_digest = artifact.program_digest;
+    let config = artifact.config.clone();
+    let output = artifact.io.output_json();
+    match artifact.verify() {
+        Ok(()) => Ok(json!({
+            "valid": true,
+            "program": program,
+            "program_digest": program_digest,
+            "config": config,
+            "output": output,
+        })),
+        Err(e) => Err(json!({ "valid": false, "program": program, "error": e.to_string() })),
+    }
+}
+
+fn inspect(path: &Path) -> Report {
+    let value: Value = serde_json::from_str(&read(path)?).map_err(error)?;
+
+    if value.get("body").is_some() || value.get("approvals_after_next").is_some() {
+        let block = read_block(path)?;
+        let approvals = &block.approvals_after_next;
+        Ok(json!({
+            "kind": "light_client_block",
+            "height": block.inner_lite.height,
+            "block_hash": base58(&block.header().hash()),
+            "epoch_id": base58(&block.inner_lite.epoch_id),
+            "next_epoch_id": base58(&block.inner_lite.next_epoch_id),
+            "next_bps": block.next_bps.as_ref().map(Vec::len),
+            "approvals": approvals.iter().flatten().count(),
+            "approval_seats": approvals.len(),
+        }))
+    } else if value.get("outcome_proof").is_some() {
+        let proof = read_proof(path)?;
+        Ok(json!({
+            "kind": "inclusion_proof",
+            "block_hash": base58(&proof.outcome_proof.block_hash),
+            "height": proof.block_header_lite.inner_lite.height,
+            "outcome_id": base58(&proof.outcome_proof.id),
+            "executor_id": proof.outcome_proof.outcome.executor_id,
+            "outcome_root_proof": proof.outcome_root_proof.len(),
+            "block_proof": proof.block_proof.len(),
+        }))
+    } else if let Some(request) = value.get("proof_request") {
+        Ok(json!({
+            "kind": "job",
+            "id": value["id"],
+            "status": value["status"],
+            "type": request["type"],
+            "input": request["data"]["input"],
+        }))
+    } else {
+        Err(error(format!("{}: unrecognized fixture", path.display())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{fixture, sync_input};
+
+    #[test]
+    fn test_parse_hash() {
+        let hash = parse_hash("11111111111111111111111111111111").unwrap();
+        assert_eq!(hash, [0; 32]);
+        assert_eq!(
+            parse_hash(&format!("0x{}", hex::encode([7; 32]))),
+            Ok([7; 32])
+        );
+        assert!(parse_hash("not a hash").is_err());
+    }
+
+    #[test]
+    fn test_inspect() {
+        let report = inspect(&fixture("test_1.json")).unwrap();
+        assert_eq!(report["kind"], "light_client_block");
+        assert_eq!(report["height"], 154697976);
+
+        let report = inspect(&fixture("new.json")).unwrap();
+        assert_eq!(report["kind"], "inclusion_proof");
+    }
+
+    #[test]
+    fn test_dry_run_sync() {
+        let report = run(
+            Command::Sync {
+                state: fixture("test_0.json"),
+                block: fixture("test_1.json"),
+                dry_run: true,
+                out: None,
+            },
+            &Network::Testnet,
+        )
+        .unwrap();
+        assert!(report["new_head"].is_string());
+
+        let report = run(
+            Command::Sync {
+                state: fixture("test_1.json"),
+                block: fixture("test_0.json"),
+                dry_run: true,
+                out: None,
+            },
+            &Network::Testnet,
+        );
+        assert_eq!(report.unwrap_err()["error"], "BlockAlreadyVerified");
+    }
+
+    #[test]
+    fn test_synced_event_reports_bps_diff() {
+        let output = Verifier::new(Network::Testnet.config())
+            .dry_run(sync_input())
+            .unwrap();
+        let report = event_report(Event::Synced(output.clone()));
+
+        // The block ends its epoch, so it carries the producers of the next
+        assert!(report["bps_diff"]["joined"].is_array());
+        assert_eq!(report["bps_diff"], json!(output.bps_diff));
+        assert_eq!(report["participation"], json!(output.participation));
+    }
+
+    #[test]
+    fn test_custom_checkpoint() {
+        let parse = |args: &[&str]| {
+            let path = fixture("test_0.json");
+            let mut argv = vec!["near-lc-jolt", "--checkpoint", path.to_str().unwrap()];
+            argv.extend(args);
+            argv.extend(["inspect", "test_1.json"]);
+            Cli::try_parse_from(argv)
+        };
+
+        let network = parse(&["--num-shards", "6"]).unwrap().network().unwrap();
+        assert!(matches!(network, Network::Custom { .. }));
+        assert_eq!(network.checkpoint(), Network::Testnet.checkpoint());
+        assert_eq!(network.config().num_shards, 6);
+        // Without a shard count, the named network's
+        let network = parse(&["--network", "mainnet"]).unwrap().network().unwrap();
+        assert_eq!(network.config(), Network::Mainnet.config());
+        assert_eq!(network.checkpoint(), Network::Testnet.checkpoint());
+
+        let cli = Cli::try_parse_from([
+            "near-lc-jolt",
+            "--checkpoint",
+            "missing.json",
+            "inspect",
+            "x",
+        ]);
+        assert!(cli.unwrap().network().is_err());
+        let cli = Cli::try_parse_from(["near-lc-jolt", "--num-shards", "6", "inspect", "x"]);
+        assert!(cli.is_err());
+    }
+}
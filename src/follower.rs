@@ -0,0 +1,363 @@
+//! Keeps a light client synced: polls the RPC for the next light client block
+//! from the stored head, checks the transition natively, proves it, persists
+//! the new head with its proof and advances.
+
+use crate::artifact::{self, ArtifactError};
+use crate::rpc::{NearRpc, RpcError};
+use crate::store::{HeaderStore, StoreError};
+use crate::verifier::{SyncInput, Verifier};
+use light_client_core::{Error, SyncOutput, TrustedState};
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone)]
+pub struct FollowerConfig {
+    /// How long to wait for a new block once synced to the node's head.
+    pub poll_interval: Duration,
+    /// First wait after a failure, doubled on each failure after.
+    pub min_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Check each transition natively and advance in memory, without proving
+    /// or storing it.
+    pub dry_run: bool,
+}
+
+impl Default for FollowerConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(60),
+            min_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(600),
+            dry_run: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FollowError {
+    Rpc(RpcError),
+    /// The node's next block doesn't sync from the head.
+    Sync(Error),
+    Artifact(ArtifactError),
+    Store(StoreError),
+    /// The store is empty and there's no checkpoint to start from.
+    NoCheckpoint,
+}
+
+impl fmt::Display for FollowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rpc(e) => write!(f, "{}", e),
+            Self::Sync(e) => write!(f, "next block doesn't sync: {:?}", e),
+            Self::Artifact(e) => write!(f, "{}", e),
+            Self::Store(e) => write!(f, "{}", e),
+            Self::NoCheckpoint => write!(f, "store is empty and network has no checkpoint"),
+        }
+    }
+}
+
+impl From<RpcError> for FollowError {
+    fn from(e: RpcError) -> Self {
+        Self::Rpc(e)
+    }
+}
+
+impl From<StoreError> for FollowError {
+    fn from(e: StoreError) -> Self {
+        Self::Store(e)
+    }
+}
+
+impl From<ArtifactError> for FollowError {
+    fn from(e: ArtifactError) -> Self {
+        Self::Artifact(e)
+    }
+}
+
+#[derive(Debug)]
+pub enum Event {
+    Synced(SyncOutput),
+    /// The node has no block past the head.
+    UpToDate,
+    Failed {
+        error: FollowError,
+        retry_in: Duration,
+    },
+}
+
+/// Exponential backoff between `min` and `max`.
+#[derive(Debug, Clone)]
+struct Backoff {
+    min: Duration,
+    max: Duration,
+    current: Option<Duration>,
+}
+
+impl Backoff {
+    fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max,
+            current: None,
+        }
+    }
+
+    fn next(&mut self) -> Duration {
+        let next = self
+            .current
+            .map_or(self.min, |current| (current * 2).min(self.max));
+        self.current = Some(next);
+        next
+    }
+
+    fn reset(&mut self) {
+        self.current = None;
+    }
+}
+
+pub struct Follower<R, S> {
+    rpc: R,
+    store: S,
+    verifier: Verifier,
+    config: FollowerConfig,
+    state: TrustedState,
+}
+
+impl<R: NearRpc, S: HeaderStore> Follower<R, S> {
+    /// Follow from the store's latest head once it's recovered, or from
+    /// `checkpoint` if the store is empty, which then needs one.
+    pub fn resume(
+        rpc: R,
+        mut store: S,
+        verifier: Verifier,
+        config: FollowerConfig,
+        checkpoint: Option<TrustedState>,
+    ) -> Result<Self, FollowError> {
+        let state = match store.recover()? {
+            Some(state) => state,
+            None => {
+                let checkpoint = checkpoint.ok_or(FollowError::NoCheckpoint)?;
+                if !config.dry_run {
+                    store.commit(&checkpoint, None)?;
+                }
+                checkpoint
+            }
+        };
+        Ok(Self {
+            rpc,
+            store,
+            verifier,
+            config,
+            state,
+        })
+    }
+
+    pub fn state(&self) -> &TrustedState {
+        &self.state
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Sync to the node's next light client block, if it has one.
+    pub fn step(&mut self) -> Result<Option<SyncOutput>, FollowError> {
+        let Some(next_block) = self.rpc.next_light_client_block(&self.state.head.hash())? else {
+            return Ok(None);
+        };
+        let input = SyncInput {
+            head: self.state.head.clone(),
+            epoch_bps: self.state.epoch_bps.clone(),
+            next_block,
+        };
+        // Fail fast on a bad block rather than after proving it
+        let output = self
+            .verifier
+            .dry_run(input.clone())
+            .map_err(FollowError::Sync)?;
+        let next = input.next_state(&output);
+
+        if !self.config.dry_run {
+            let artifact = artifact::prove_sync(input, self.verifier.config().clone());
+            // Verified before it's stored
+            artifact.verify()?;
+            self.store.commit(&next, Some(&artifact))?;
+        }
+        self.state = next;
+        Ok(Some(output))
+    }
+
+    /// Step until `shutdown` is set, backing off on failures. A step in
+    /// progress is finished first, so the store is never left mid-commit.
+    pub fn run(&mut self, shutdown: &AtomicBool, mut on_event: impl FnMut(Event)) {
+        let mut backoff = Backoff::new(self.config.min_backoff, self.config.max_backoff);
+        while !shutdown.load(Ordering::SeqCst) {
+            let wait = match self.step() {
+                Ok(Some(output)) => {
+                    backoff.reset();
+                    on_event(Event::Synced(output));
+                    // Catch up without waiting
+                    Duration::ZERO
+                }
+                Ok(None) => {
+                    backoff.reset();
+                    on_event(Event::UpToDate);
+                    self.config.poll_interval
+                }
+                Err(error) => {
+                    let retry_in = backoff.next();
+                    on_event(Event::Failed { error, retry_in });
+                    retry_in
+                }
+            };
+            sleep(wait, shutdown);
+        }
+    }
+}
+
+/// Sleep for `duration`, waking early on `shutdown`.
+fn sleep(duration: Duration, shutdown: &AtomicBool) {
+    let deadline = Instant::now() + duration;
+    while !shutdown.load(Ordering::SeqCst) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        thread::sleep(remaining.min(Duration::from_millis(100)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::HttpRpc;
+    use crate::stand_in::{ChainRpc, StandIn};
+    use crate::store::FileStore;
+    use crate::test_util::store;
+    use chain_generator::{Chain, ChainConfig};
+    use light_client_core::Network;
+    use std::fs;
+
+    fn config() -> FollowerConfig {
+        FollowerConfig {
+            poll_interval: Duration::from_millis(1),
+            min_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(4),
+            dry_run: true,
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+        let waits: Vec<_> = (0..5).map(|_| backoff.next().as_secs()).collect();
+        assert_eq!(waits, [1, 2, 4, 5, 5]);
+
+        backoff.reset();
+        assert_eq!(backoff.next(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_resume_without_checkpoint() {
+        let chain = Chain::generate(ChainConfig::default());
+        let mut store = store("follow-resume");
+        let resume = |store: FileStore| {
+            Follower::resume(
+                ChainRpc::new(chain.clone()),
+                store,
+                Verifier::new(Network::Localnet.config()),
+                config(),
+                None,
+            )
+        };
+        assert!(matches!(
+            resume(store.clone()),
+            Err(FollowError::NoCheckpoint)
+        ));
+
+        // A stored head needs no checkpoint to resume from
+        store.commit(&chain.genesis_state(), None).unwrap();
+        assert_eq!(
+            resume(store.clone()).unwrap().state(),
+            &chain.genesis_state()
+        );
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_follow_stand_in() {
+        let chain = Chain::generate(ChainConfig {
+            rotation: 1,
+            ..Default::default()
+        });
+        let stand_in = StandIn::spawn(ChainRpc::new(chain.clone()), "127.0.0.1:0").unwrap();
+        let store = store("follow");
+        let mut follower = Follower::resume(
+            HttpRpc::new(stand_in.url()),
+            store,
+            Verifier::new(Network::Localnet.config()),
+            config(),
+            Some(chain.genesis_state()),
+        )
+        .unwrap();
+
+        let shutdown = AtomicBool::new(false);
+        let mut synced = 0;
+        follower.run(&shutdown, |event| match event {
+            Event::Synced(_) => synced += 1,
+            Event::UpToDate => shutdown.store(true, Ordering::SeqCst),
+            Event::Failed { error, .. } => panic!("{}", error),
+        });
+
+        assert_eq!(synced, chain.config.epochs);
+        assert_eq!(
+            follower.state().head.hash(),
+            chain.light_client_blocks.last().unwrap().header().hash()
+        );
+        // Dry runs leave the store as it was
+        assert_eq!(follower.store().latest_hash().unwrap(), None);
+        fs::remove_dir_all(follower.store().dir()).unwrap();
+    }
+
+    #[test]
+    fn test_back_off_on_rpc_errors() {
+        let chain = Chain::generate(ChainConfig::default());
+        let stand_in = StandIn::spawn(ChainRpc::new(chain), "127.0.0.1:0").unwrap();
+        // A head the node doesn't know
+        let other = Chain::generate(ChainConfig {
+            seed: 1,
+            ..Default::default()
+        });
+        let store = store("follow-backoff");
+        let mut follower = Follower::resume(
+            HttpRpc::new(stand_in.url()),
+            store,
+            Verifier::new(Network::Localnet.config()),
+            config(),
+            Some(other.genesis_state()),
+        )
+        .unwrap();
+
+        let shutdown = AtomicBool::new(false);
+        let mut retries = Vec::new();
+        follower.run(&shutdown, |event| match event {
+            Event::Failed {
+                error: FollowError::Rpc(RpcError::NotFound),
+                retry_in,
+            } => {
+                retries.push(retry_in.as_millis());
+                if retries.len() == 4 {
+                    shutdown.store(true, Ordering::SeqCst);
+                }
+            }
+            event => panic!("unexpected {:?}", event),
+        });
+
+        assert_eq!(retries, [1, 2, 4, 4]);
+        fs::remove_dir_all(follower.store().dir()).unwrap();
+    }
+}
@@ -0,0 +1,18 @@
+pub mod artifact;
+pub mod cli;
+pub mod follower;
+pub mod mutation;
+pub mod profile;
+pub mod program;
+pub mod prover_service;
+pub mod recorder;
+pub mod rpc;
+pub mod server;
+pub mod stand_in;
+pub mod store;
+pub mod verifier;
+
+#[cfg(test)]
+mod test_util;
+
+pub use verifier::Verifier;
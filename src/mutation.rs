@@ -0,0 +1,343 @@
+//! Systematic mutations of a valid sync transition, each of which
+//! `Protocol::sync` must reject with a known `Error`.
+
+use crate::verifier::SyncInput;
+use light_client_core::{Error, Hash, Protocol};
+use std::fmt;
+use types::ValidatorStakeView;
+
+type Mutate = Box<dyn Fn(&mut SyncInput)>;
+
+pub struct Mutation {
+    pub name: String,
+    pub expected: Error,
+    mutate: Mutate,
+}
+
+impl Mutation {
+    pub fn new(
+        name: impl Into<String>,
+        expected: Error,
+        mutate: impl Fn(&mut SyncInput) + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            expected,
+            mutate: Box::new(mutate),
+        }
+    }
+
+    pub fn apply(&self, input: &SyncInput) -> SyncInput {
+        let mut input = input.clone();
+        (self.mutate)(&mut input);
+        input
+    }
+}
+
+/// A mutation `Protocol::sync` did not reject as expected.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Finding {
+    Accepted {
+        name: String,
+    },
+    WrongError {
+        name: String,
+        expected: Error,
+        actual: Error,
+    },
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Accepted { name } => write!(f, "{}: accepted", name),
+            Self::WrongError {
+                name,
+                expected,
+                actual,
+            } => write!(f, "{}: expected {:?}, got {:?}", name, expected, actual),
+        }
+    }
+}
+
+/// Flip each byte of a hash field of the input in turn.
+fn flip_bytes(
+    mutations: &mut Vec<Mutation>,
+    field: &str,
+    expected: Error,
+    get: fn(&mut SyncInput) -> &mut Hash,
+) {
+    for i in 0..32 {
+        mutations.push(Mutation::new(
+            format!("{}[{}] flipped", field, i),
+            expected.clone(),
+            move |input| get(input)[i] ^= 0xff,
+        ));
+    }
+}
+
+/// Mutations of a valid transition from a head to a block of its next epoch.
+pub fn mutations() -> Vec<Mutation> {
+    use Error::*;
+
+    let mut mutations = Vec::new();
+
+    // Any change to the hashed header is a change to the approval message
+    flip_bytes(
+        &mut mutations,
+        "prev_block_hash",
+        NotEnoughApprovedStake,
+        |input| &mut input.next_block.prev_block_hash,
+    );
+    flip_bytes(
+        &mut mutations,
+        "next_block_inner_hash",
+        NotEnoughApprovedStake,
+        |input| &mut input.next_block.next_block_inner_hash,
+    );
+    flip_bytes(
+        &mut mutations,
+        "inner_rest_hash",
+        NotEnoughApprovedStake,
+        |input| &mut input.next_block.inner_rest_hash,
+    );
+    flip_bytes(
+        &mut mutations,
+        "epoch_id",
+        BlockNotCurrentOrNextEpoch,
+        |input| &mut input.next_block.inner_lite.epoch_id,
+    );
+    flip_bytes(
+        &mut mutations,
+        "next_epoch_id",
+        NotEnoughApprovedStake,
+        |input| &mut input.next_block.inner_lite.next_epoch_id,
+    );
+    flip_bytes(
+        &mut mutations,
+        "prev_state_root",
+        NotEnoughApprovedStake,
+        |input| &mut input.next_block.inner_lite.prev_state_root,
+    );
+    flip_bytes(
+        &mut mutations,
+        "outcome_root",
+        NotEnoughApprovedStake,
+        |input| &mut input.next_block.inner_lite.outcome_root,
+    );
+    flip_bytes(
+        &mut mutations,
+        "next_bp_hash",
+        NotEnoughApprovedStake,
+        |input| &mut input.next_block.inner_lite.next_bp_hash,
+    );
+    flip_bytes(
+        &mut mutations,
+        "block_merkle_root",
+        NotEnoughApprovedStake,
+        |input| &mut input.next_block.inner_lite.block_merkle_root,
+    );
+    // The legacy `timestamp` is not hashed, so only `timestamp_nanosec` is mutated
+    mutations.push(Mutation::new(
+        "timestamp_nanosec incremented",
+        NotEnoughApprovedStake,
+        |input| input.next_block.inner_lite.timestamp_nanosec += 1,
+    ));
+
+    // Heights
+    mutations.push(Mutation::new(
+        "height set to head",
+        BlockAlreadyVerified,
+        |input| input.next_block.inner_lite.height = input.head.inner_lite.height,
+    ));
+    mutations.push(Mutation::new(
+        "height below head",
+        BlockAlreadyVerified,
+        |input| input.next_block.inner_lite.height = input.head.inner_lite.height - 1,
+    ));
+    mutations.push(Mutation::new(
+        "height incremented",
+        NotEnoughApprovedStake,
+        |input| input.next_block.inner_lite.height += 1,
+    ));
+    mutations.push(Mutation::new(
+        "head height raised",
+        BlockAlreadyVerified,
+        |input| input.head.inner_lite.height = input.next_block.inner_lite.height,
+    ));
+
+    // Epoch ids
+    mutations.push(Mutation::new(
+        "epoch_id set to head's current epoch",
+        NotEnoughApprovedStake,
+        |input| input.next_block.inner_lite.epoch_id = input.head.inner_lite.epoch_id,
+    ));
+    mutations.push(Mutation::new(
+        "head next_epoch_id flipped",
+        BlockNotCurrentOrNextEpoch,
+        |input| input.head.inner_lite.next_epoch_id[0] ^= 0xff,
+    ));
+
+    // Signatures
+    mutations.push(Mutation::new(
+        "all signatures dropped",
+        NotEnoughApprovedStake,
+        |input| {
+            input
+                .next_block
+                .approvals_after_next
+                .iter_mut()
+                .for_each(|approval| *approval = None)
+        },
+    ));
+    mutations.push(Mutation::new(
+        "signatures dropped down to two thirds of stake",
+        NotEnoughApprovedStake,
+        drop_signatures_to_threshold,
+    ));
+    mutations.push(Mutation::new(
+        "approvals truncated",
+        NotEnoughApprovedStake,
+        |input| input.next_block.approvals_after_next.clear(),
+    ));
+    mutations.push(Mutation::new(
+        "signatures reversed",
+        NotEnoughApprovedStake,
+        |input| {
+            let len = input.epoch_bps.len();
+            input.next_block.approvals_after_next.truncate(len);
+            input.next_block.approvals_after_next.reverse()
+        },
+    ));
+    mutations.push(Mutation::new(
+        "signatures rotated",
+        NotEnoughApprovedStake,
+        |input| input.next_block.approvals_after_next.rotate_left(1),
+    ));
+    mutations.push(Mutation::new(
+        "signatures replaced by another's",
+        NotEnoughApprovedStake,
+        |input| {
+            let approvals = &mut input.next_block.approvals_after_next;
+            let Some(first) = approvals.iter().flatten().next().cloned() else {
+                return;
+            };
+            for signature in approvals.iter_mut().flatten() {
+                *signature = first.clone();
+            }
+        },
+    ));
+
+    // Block producers
+    mutations.push(Mutation::new(
+        "epoch_bps reversed",
+        NotEnoughApprovedStake,
+        |input| input.epoch_bps.reverse(),
+    ));
+    mutations.push(Mutation::new("next_bps removed", NextBpsInvalid, |input| {
+        input.next_block.next_bps = None
+    }));
+    mutations.push(Mutation::new(
+        "next_bps entries swapped",
+        NextBpsInvalid,
+        |input| {
+            if let Some(next_bps) = input.next_block.next_bps.as_mut() {
+                next_bps.swap(0, 1)
+            }
+        },
+    ));
+    mutations.push(Mutation::new(
+        "next_bps entry dropped",
+        NextBpsInvalid,
+        |input| {
+            if let Some(next_bps) = input.next_block.next_bps.as_mut() {
+                next_bps.pop();
+            }
+        },
+    ));
+    mutations.push(Mutation::new(
+        "next_bps stake changed",
+        NextBpsInvalid,
+        |input| {
+            if let Some(next_bps) = input.next_block.next_bps.as_mut() {
+                let ValidatorStakeView::V1(bp) = &mut next_bps[0];
+                bp.stake += 1;
+            }
+        },
+    ));
+
+    mutations
+}
+
+/// Drop approvals, in seat order, until what remains is no more than two
+/// thirds of the stake.
+fn drop_signatures_to_threshold(input: &mut SyncInput) {
+    let message = Protocol::reconstruct_approval_message(&input.next_block).unwrap();
+    let (stake, participation) = Protocol::validate_approvals(
+        &input.next_block.approvals_after_next,
+        &input.epoch_bps,
+        &message,
+    );
+    let threshold = stake.total / 3 * 2;
+
+    let mut approved = stake.approved;
+    for (i, bp) in input.epoch_bps.iter().enumerate() {
+        if approved <= threshold {
+            break;
+        }
+        if participation.approved(i) {
+            input.next_block.approvals_after_next[i] = None;
+            approved -= bp.stake;
+        }
+    }
+}
+
+/// Apply every mutation to `input`, returning the ones not rejected as
+/// expected.
+pub fn run(input: &SyncInput) -> Vec<Finding> {
+    mutations()
+        .into_iter()
+        .filter_map(|mutation| {
+            let mutated = mutation.apply(input);
+            match Protocol::sync(&mutated.head, &mutated.epoch_bps, mutated.next_block) {
+                Ok(_) => Some(Finding::Accepted {
+                    name: mutation.name,
+                }),
+                Err(actual) if actual != mutation.expected => Some(Finding::WrongError {
+                    name: mutation.name,
+                    expected: mutation.expected,
+                    actual,
+                }),
+                Err(_) => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::sync_input;
+    use crate::verifier::Verifier;
+    use light_client_core::Network;
+
+    #[test]
+    fn test_mutations_rejected() {
+        let input = sync_input();
+        assert!(Verifier::new(Network::Testnet.config())
+            .dry_run(input.clone())
+            .is_ok());
+
+        let findings = run(&input);
+
+        assert!(
+            findings.is_empty(),
+            "{}",
+            findings
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Per-phase cycle breakdown of a guest run, from the markers placed around
+//! each phase of `Protocol`.
+
+use crate::verifier::{InclusionInput, SyncInput};
+use light_client_core::{
+    profile::{INCLUSION_PHASES, NESTED_PHASES, SYNC_PHASES},
+    CompactLightClientBlock, ProtocolConfig,
+};
+use std::{fmt, io::Read};
+
+/// The phase of deserializing a guest's inputs. The guest does so before any
+/// marker can be placed, so it's measured as the run of a harness guest that
+/// only deserializes the same inputs.
+pub const DESERIALIZE: &str = "deserialize";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseReport {
+    pub total_cycles: u64,
+    /// Cycles spent in each phase: deserialization, then those of `Protocol`
+    /// in order.
+    pub phases: Vec<(&'static str, u64)>,
+}
+
+impl PhaseReport {
+    /// Cycles outside any phase: output serialization and the glue in between.
+    pub fn untracked_cycles(&self) -> u64 {
+        let tracked: u64 = self
+            .phases
+            .iter()
+            .filter(|(label, _)| !NESTED_PHASES.contains(label))
+            .map(|(_, cycles)| cycles)
+            .sum();
+        self.total_cycles.saturating_sub(tracked)
+    }
+
+    /// The tracer reports a marker as `"<label>": <cycles> RV32IM cycles, ...`.
+    fn from_tracer_output(total_cycles: u64, labels: &[&'static str], output: &str) -> Self {
+        let phases = labels
+            .iter()
+            .map(|label| {
+                let prefix = format!("\"{}\": ", label);
+                let cycles = output
+                    .lines()
+                    .filter_map(|line| line.trim().strip_prefix(&prefix))
+                    .filter_map(|rest| rest.split_whitespace().next()?.parse::<u64>().ok())
+                    .sum();
+                (*label, cycles)
+            })
+            .collect();
+        Self {
+            total_cycles,
+            phases,
+        }
+    }
+}
+
+impl fmt::Display for PhaseReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = |cycles: u64| cycles as f64 * 100.0 / self.total_cycles.max(1) as f64;
+        for (label, cycles) in &self.phases {
+            writeln!(f, "{:<24} {:>12} {:>6.2}%", label, cycles, percent(*cycles))?;
+        }
+        let untracked = self.untracked_cycles();
+        writeln!(
+            f,
+            "{:<24} {:>12} {:>6.2}%",
+            "untracked",
+            untracked,
+            percent(untracked)
+        )?;
+        write!(f, "{:<24} {:>12}", "total", self.total_cycles)
+    }
+}
+
+/// Trace `f` while capturing stdout, where the tracer prints marker cycles,
+/// after the `deserialize` cycles of its inputs.
+fn trace_phases(labels: &[&'static str], deserialize: u64, f: impl FnOnce() -> u64) -> PhaseReport {
+    let mut captured = gag::BufferRedirect::stdout().unwrap();
+    let total_cycles = f();
+    let mut output = String::new();
+    captured.read_to_string(&mut output).unwrap();
+    drop(captured);
+
+    let mut report = PhaseReport::from_tracer_output(total_cycles, labels, &output);
+    report.phases.insert(0, (DESERIALIZE, deserialize));
+    report
+}
+
+pub fn sync_report(input: SyncInput, config: ProtocolConfig) -> PhaseReport {
+    let deserialize = guest_harness::analyze_deserialize_sync_lc(
+        input.head.clone(),
+        input.epoch_bps.clone(),
+        input.next_block.clone(),
+        config.clone(),
+    )
+    .trace_len() as u64;
+    trace_phases(SYNC_PHASES, deserialize, || {
+        guest_sync::analyze_sync_lc(input.head, input.epoch_bps, input.next_block, config)
+            .trace_len() as u64
+    })
+}
+
+/// As `sync_report`, but through `sync_lc_compact`.
+pub fn compact_sync_report(input: SyncInput, config: ProtocolConfig) -> PhaseReport {
+    let next_block = CompactLightClientBlock::from(input.next_block);
+    let deserialize = guest_harness::analyze_deserialize_sync_lc_compact(
+        input.head.clone(),
+        input.epoch_bps.clone(),
+        next_block.clone(),
+        config.clone(),
+    )
+    .trace_len() as u64;
+    trace_phases(SYNC_PHASES, deserialize, || {
+        guest_sync::analyze_sync_lc_compact(input.head, input.epoch_bps, next_block, config)
+            .trace_len() as u64
+    })
+}
+
+pub fn inclusion_report(input: InclusionInput, config: ProtocolConfig) -> PhaseReport {
+    let deserialize =
+        guest_harness::analyze_deserialize_inclusion(input.proof.clone(), config.clone())
+            .trace_len() as u64;
+    trace_phases(INCLUSION_PHASES, deserialize, || {
+        guest_inclusion::analyze_inclusion(input.proof, config).trace_len() as u64
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_tracer_output() {
+        let output = "\"sync:checks\": 120 RV32IM cycles, 130 virtual cycles\n\
+                      some other line\n\
+                      \"sync:signatures\": 8000 RV32IM cycles, 9000 virtual cycles\n";
+
+        let report = PhaseReport::from_tracer_output(10_000, SYNC_PHASES, output);
+
+        assert_eq!(report.phases[0], ("sync:checks", 120));
+        assert_eq!(report.phases[3], ("sync:signatures", 8000));
+        assert_eq!(report.untracked_cycles(), 1880);
+    }
+}
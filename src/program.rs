@@ -0,0 +1,185 @@
+//! Identifiers of the guest programs a proof can be of: every entrypoint of
+//! the sync, inclusion and batch guests. The harness guest's entrypoints only
+//! exercise single checks and profile deserialization, so are never stored.
+
+use ark_serialize::CanonicalSerialize;
+use light_client_core::{hash, Hash};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, sync::OnceLock};
+
+/// The guest entrypoint a proof is of.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Program {
+    SyncLc,
+    SyncLcCompact,
+    VerifyInnerRest,
+    RandomBeacon,
+    Inclusion,
+    InclusionPredicate,
+    SyncAndInclude,
+    InclusionBatch,
+}
+
+impl Program {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SyncLc => "sync_lc",
+            Self::SyncLcCompact => "sync_lc_compact",
+            Self::VerifyInnerRest => "verify_inner_rest",
+            Self::RandomBeacon => "random_beacon",
+            Self::Inclusion => "inclusion",
+            Self::InclusionPredicate => "inclusion_predicate",
+            Self::SyncAndInclude => "sync_and_include",
+            Self::InclusionBatch => "inclusion_batch",
+        }
+    }
+
+    /// Digest of the compiled guest and its preprocessing, so a proof can be
+    /// tied to the exact build that produced it. Computed once per process, as
+    /// it builds and preprocesses the guest.
+    pub fn digest(&self) -> ProgramDigest {
+        macro_rules! digest {
+            ($name:expr, $compile:path, $preprocess:path) => {{
+                static DIGEST: OnceLock<ProgramDigest> = OnceLock::new();
+                *DIGEST.get_or_init(|| {
+                    let mut program = $compile();
+                    let preprocessing = $preprocess(&mut program);
+                    ProgramDigest::new($name, &program, &preprocessing)
+                })
+            }};
+        }
+
+        let name = self.name();
+        match self {
+            Self::SyncLc => digest!(
+                name,
+                guest_sync::compile_sync_lc,
+                guest_sync::preprocess_sync_lc
+            ),
+            Self::SyncLcCompact => digest!(
+                name,
+                guest_sync::compile_sync_lc_compact,
+                guest_sync::preprocess_sync_lc_compact
+            ),
+            Self::VerifyInnerRest => digest!(
+                name,
+                guest_sync::compile_verify_inner_rest,
+                guest_sync::preprocess_verify_inner_rest
+            ),
+            Self::RandomBeacon => digest!(
+                name,
+                guest_sync::compile_random_beacon,
+                guest_sync::preprocess_random_beacon
+            ),
+            Self::Inclusion => digest!(
+                name,
+                guest_inclusion::compile_inclusion,
+                guest_inclusion::preprocess_inclusion
+            ),
+            Self::InclusionPredicate => digest!(
+                name,
+                guest_inclusion::compile_inclusion_predicate,
+                guest_inclusion::preprocess_inclusion_predicate
+            ),
+            Self::SyncAndInclude => digest!(
+                name,
+                guest_inclusion::compile_sync_and_include,
+                guest_inclusion::preprocess_sync_and_include
+            ),
+            Self::InclusionBatch => digest!(
+                name,
+                guest_batch::compile_inclusion_batch,
+                guest_batch::preprocess_inclusion_batch
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramDigest(pub Hash);
+
+impl ProgramDigest {
+    fn new(
+        name: &str,
+        program: &jolt::host::Program,
+        preprocessing: &impl CanonicalSerialize,
+    ) -> Self {
+        let elf = program
+            .elf
+            .as_ref()
+            .expect("guest is built by preprocessing");
+        let elf = std::fs::read(elf).expect("guest ELF is readable");
+
+        let mut bytes = Vec::new();
+        preprocessing
+            .serialize_compressed(&mut bytes)
+            .expect("preprocessing serializes");
+
+        Self::from_parts(name, &elf, &bytes)
+    }
+
+    fn from_parts(name: &str, elf: &[u8], preprocessing: &[u8]) -> Self {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&hash(elf));
+        bytes.extend_from_slice(&hash(preprocessing));
+        Self(hash(&bytes))
+    }
+}
+
+impl fmt::Display for ProgramDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+impl Serialize for ProgramDigest {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ProgramDigest {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(&s)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(Self)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid program digest: {}", s)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_binds_every_part() {
+        let digest = ProgramDigest::from_parts("sync_lc", b"elf", b"preprocessing");
+
+        assert_eq!(
+            digest,
+            ProgramDigest::from_parts("sync_lc", b"elf", b"preprocessing")
+        );
+        assert_ne!(
+            digest,
+            ProgramDigest::from_parts("inclusion", b"elf", b"preprocessing")
+        );
+        assert_ne!(
+            digest,
+            ProgramDigest::from_parts("sync_lc", b"elf2", b"preprocessing")
+        );
+        assert_ne!(
+            digest,
+            ProgramDigest::from_parts("sync_lc", b"elf", b"preprocessing2")
+        );
+
+        let json = serde_json::to_string(&digest).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ProgramDigest>(&json).unwrap(),
+            digest
+        );
+    }
+}
@@ -0,0 +1,548 @@
+//! A prover job service speaking the proof-request schema of the hosted
+//! prover we used before, as recorded in `fixtures/sync_proof.json`: jobs are
+//! posted as `req_bytes` requests, queued, proven one at a time on the host
+//! and polled for their status and result.
+//!
+//! ```text
+//! POST /proofs        {"type": "req_bytes", "releaseId": .., "data": {"input": "0x.."}}
+//! GET  /proofs        every job, oldest first
+//! GET  /proofs/<id>   one job
+//! ```
+
+use crate::artifact::{self, ArtifactError, ProofArtifact};
+use crate::program::Program;
+use crate::rpc::{NearRpc, ProofId};
+use crate::server::JsonServer;
+use crate::store::HeaderStore;
+use crate::verifier::{BatchInput, SyncInput, Verifier};
+use light_client_core::{Hash, Header};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::VecDeque,
+    io,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex},
+    thread::{self, JoinHandle},
+    time::SystemTime,
+};
+use types::{Request, SyncRequest, VerifyRequest};
+
+/// A request for a proof, as the hosted service took it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProofRequest {
+    /// Guest input as raw bytes, hex-encoded.
+    ReqBytes {
+        #[serde(rename = "releaseId")]
+        release_id: String,
+        data: RequestData,
+    },
+}
+
+impl ProofRequest {
+    pub fn release_id(&self) -> &str {
+        match self {
+            Self::ReqBytes { release_id, .. } => release_id,
+        }
+    }
+
+    pub fn input(&self) -> &[u8] {
+        match self {
+            Self::ReqBytes { data, .. } => &data.input,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RequestData {
+    #[serde(with = "hex_bytes")]
+    pub input: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Success,
+    Failure,
+}
+
+/// What a successful job proved: the artifact, as `ProofArtifact::write_to`
+/// encodes it, and its public output for callers that don't verify it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct JobResult {
+    pub program: Program,
+    pub output: Value,
+    #[serde(with = "hex_bytes")]
+    pub proof: Vec<u8>,
+}
+
+impl JobResult {
+    pub fn from_artifact(artifact: &ProofArtifact) -> Result<Self, ArtifactError> {
+        let mut proof = Vec::new();
+        artifact.write_to(&mut proof)?;
+        Ok(Self {
+            program: artifact.program(),
+            output: artifact.io.output_json(),
+            proof,
+        })
+    }
+}
+
+/// A job record. `edges` holds the release and on-chain requests the hosted
+/// service linked jobs to; jobs created here have none. `result` and `error`
+/// are only present once a job has finished.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Job {
+    pub id: String,
+    pub status: JobStatus,
+    pub proof_request: ProofRequest,
+    pub proof_release: String,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(default)]
+    pub edges: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<JobResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Serde for bytes as `0x`-prefixed hex strings.
+mod hex_bytes {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(s.strip_prefix("0x").unwrap_or(&s)).map_err(D::Error::custom)
+    }
+}
+
+fn now() -> String {
+    humantime::format_rfc3339_micros(SystemTime::now()).to_string()
+}
+
+/// Proves the input of a job.
+pub trait JobRunner {
+    fn run(&self, input: &[u8]) -> Result<JobResult, String>;
+}
+
+impl<F: Fn(&[u8]) -> Result<JobResult, String>> JobRunner for F {
+    fn run(&self, input: &[u8]) -> Result<JobResult, String> {
+        self(input)
+    }
+}
+
+/// Runs jobs on the Jolt prover, decoding their input as a `types::Request`.
+/// The trusted head of a request must be in `store`: a sync is from it to the
+/// node's next light client block after it, which is dry run before it's
+/// proven, and a verify is of the outcomes' proofs under it, as one
+/// `inclusion_batch` run.
+pub struct ProverRunner<R, S> {
+    rpc: R,
+    store: S,
+    verifier: Verifier,
+}
+
+impl<R: NearRpc, S: HeaderStore> ProverRunner<R, S> {
+    pub fn new(rpc: R, store: S, verifier: Verifier) -> Self {
+        Self {
+            rpc,
+            store,
+            verifier,
+        }
+    }
+
+    fn trusted_head(&self, trusted: &Hash) -> Result<Header, String> {
+        self.store
+            .head(trusted)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("trusted head {} is not stored", hex::encode(trusted)))
+    }
+
+    fn sync(&self, SyncRequest { trusted_head }: SyncRequest) -> Result<JobResult, String> {
+        let head = self.trusted_head(&trusted_head)?;
+        let epoch_bps = self
+            .store
+            .bps(&head.inner_lite.next_epoch_id)
+            .map_err(|e| e.to_string())?
+            .ok_or("block producers of the trusted head's next epoch are not stored")?;
+        let next_block = self
+            .rpc
+            .next_light_client_block(&trusted_head)
+            .map_err(|e| e.to_string())?
+            .ok_or("node has no light client block past the trusted head")?;
+        let input = SyncInput {
+            head,
+            epoch_bps,
+            next_block,
+        };
+        self.verifier
+            .dry_run(input.clone())
+            .map_err(|e| format!("next block doesn't sync: {:?}", e))?;
+
+        let artifact = artifact::prove_sync(input, self.verifier.config().clone());
+        artifact
+            .verify()
+            .and_then(|()| JobResult::from_artifact(&artifact))
+            .map_err(|e| e.to_string())
+    }
+
+    fn verify(
+        &self,
+        VerifyRequest {
+            trusted_head,
+            outcomes,
+        }: VerifyRequest,
+    ) -> Result<JobResult, String> {
+        let head = self.trusted_head(&trusted_head)?;
+        let proofs = outcomes
+            .into_iter()
+            .map(|outcome| {
+                let id = ProofId::from(outcome);
+                self.rpc
+                    .light_client_proof(&id, &trusted_head)
+                    .map_err(|e| format!("proof of {}: {}", hex::encode(id.outcome_id()), e))
+            })
+            .collect::<Result<_, _>>()?;
+        let input = BatchInput {
+            head_block_root: head.inner_lite.block_merkle_root,
+            proofs,
+        };
+        let size = input.guest_input_size();
+        if size > guest_batch::MAX_INPUT_SIZE {
+            return Err(format!(
+                "{} proofs take {} bytes, more than the batch guest's {}",
+                input.proofs.len(),
+                size,
+                guest_batch::MAX_INPUT_SIZE
+            ));
+        }
+
+        let artifact = artifact::prove_batch(input, self.verifier.config().clone());
+        artifact
+            .verify()
+            .and_then(|()| JobResult::from_artifact(&artifact))
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl<R: NearRpc, S: HeaderStore> JobRunner for ProverRunner<R, S> {
+    fn run(&self, input: &[u8]) -> Result<JobResult, String> {
+        match Request::decode(input).map_err(|e| e.to_string())? {
+            Request::Sync(request) => self.sync(request),
+            Request::Verify(request) => self.verify(request),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: Vec<Job>,
+    /// Indices into `jobs` of those yet to run, oldest first.
+    pending: VecDeque<usize>,
+    closed: bool,
+}
+
+/// The jobs of a service, shared between its server and its worker.
+#[derive(Clone, Default)]
+pub struct Jobs {
+    queue: Arc<(Mutex<Queue>, Condvar)>,
+}
+
+impl Jobs {
+    /// Queue `request`, returning its job as created.
+    pub fn submit(&self, request: ProofRequest) -> Job {
+        let now = now();
+        let job = Job {
+            id: uuid::Uuid::new_v4().to_string(),
+            status: JobStatus::Pending,
+            proof_release: request.release_id().to_string(),
+            proof_request: request,
+            created_at: now.clone(),
+            updated_at: now,
+            edges: json!({}),
+            result: None,
+            error: None,
+        };
+
+        let (queue, ready) = &*self.queue;
+        let mut queue = queue.lock().unwrap();
+        queue.pending.push_back(queue.jobs.len());
+        queue.jobs.push(job.clone());
+        ready.notify_one();
+        job
+    }
+
+    pub fn get(&self, id: &str) -> Option<Job> {
+        let queue = self.queue.0.lock().unwrap();
+        queue.jobs.iter().find(|job| job.id == id).cloned()
+    }
+
+    /// Every job, oldest first.
+    pub fn list(&self) -> Vec<Job> {
+        self.queue.0.lock().unwrap().jobs.clone()
+    }
+
+    /// Wait for the next pending job and mark it running, or `None` once
+    /// closed.
+    fn take(&self) -> Option<(usize, Vec<u8>)> {
+        let (queue, ready) = &*self.queue;
+        let mut queue = queue.lock().unwrap();
+        loop {
+            if queue.closed {
+                return None;
+            }
+            if let Some(index) = queue.pending.pop_front() {
+                let job = &mut queue.jobs[index];
+                job.status = JobStatus::Running;
+                job.updated_at = now();
+                return Some((index, job.proof_request.input().to_vec()));
+            }
+            queue = ready.wait(queue).unwrap();
+        }
+    }
+
+    fn finish(&self, index: usize, result: Result<JobResult, String>) {
+        let mut queue = self.queue.0.lock().unwrap();
+        let job = &mut queue.jobs[index];
+        match result {
+            Ok(result) => {
+                job.status = JobStatus::Success;
+                job.result = Some(result);
+            }
+            Err(error) => {
+                job.status = JobStatus::Failure;
+                job.error = Some(error);
+            }
+        }
+        job.updated_at = now();
+    }
+
+    /// Stop handing out jobs. Pending jobs stay pending.
+    fn close(&self) {
+        let (queue, ready) = &*self.queue;
+        queue.lock().unwrap().closed = true;
+        ready.notify_all();
+    }
+}
+
+/// Answer a request to the job API, with its HTTP status.
+pub fn respond(jobs: &Jobs, method: &str, path: &str, body: &[u8]) -> (u16, Value) {
+    let not_found = |what: &str| (404, json!({ "error": format!("no {}", what) }));
+    match (method, path.trim_end_matches('/')) {
+        ("POST", "/proofs") => match serde_json::from_slice::<ProofRequest>(body) {
+            Ok(request) => (201, json!(jobs.submit(request))),
+            Err(e) => (
+                400,
+                json!({ "error": format!("not a proof request: {}", e) }),
+            ),
+        },
+        ("GET", "/proofs") => (200, json!(jobs.list())),
+        ("GET", path) => match path.strip_prefix("/proofs/") {
+            Some(id) => jobs.get(id).map_or_else(
+                || not_found(&format!("job {}", id)),
+                |job| (200, json!(job)),
+            ),
+            None => not_found(path),
+        },
+        (_, path) => not_found(path),
+    }
+}
+
+/// A prover service answering from a background thread and proving on
+/// another until dropped. A proof in progress is finished first.
+pub struct ProverService {
+    jobs: Jobs,
+    server: JsonServer,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl ProverService {
+    /// Serve on `addr`, e.g. `127.0.0.1:0` for any free port, running jobs
+    /// on `runner`.
+    pub fn spawn(runner: impl JobRunner + Send + 'static, addr: &str) -> io::Result<Self> {
+        let jobs = Jobs::default();
+        let server = JsonServer::spawn(addr, {
+            let jobs = jobs.clone();
+            move |method: &str, path: &str, body: &[u8]| respond(&jobs, method, path, body)
+        })?;
+
+        let worker = thread::spawn({
+            let jobs = jobs.clone();
+            move || {
+                while let Some((index, input)) = jobs.take() {
+                    // A panicking prover fails its job rather than the worker
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| runner.run(&input)))
+                        .unwrap_or_else(|_| Err("prover panicked".into()));
+                    jobs.finish(index, result);
+                }
+            }
+        });
+
+        Ok(Self {
+            jobs,
+            server,
+            worker: Some(worker),
+        })
+    }
+
+    pub fn url(&self) -> String {
+        self.server.url()
+    }
+
+    pub fn jobs(&self) -> &Jobs {
+        &self.jobs
+    }
+
+    /// Serve until the process is killed.
+    pub fn wait(mut self) {
+        self.server.wait();
+    }
+}
+
+impl Drop for ProverService {
+    fn drop(&mut self) {
+        self.jobs.close();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::FixtureRpc;
+    use crate::test_util::{fixture_json, fixtures, store};
+    use light_client_core::Network;
+    use std::{fs, time::Duration};
+
+    /// Proves 32-byte inputs instantly and fails the rest.
+    fn fake_runner(input: &[u8]) -> Result<JobResult, String> {
+        if input.len() != 32 {
+            return Err(format!("{} bytes", input.len()));
+        }
+        Ok(JobResult {
+            program: Program::SyncLc,
+            output: json!({ "trusted": hex::encode(input) }),
+            proof: input.to_vec(),
+        })
+    }
+
+    fn wait_for(url: &str) -> Job {
+        for _ in 0..100 {
+            let job: Job = ureq::get(url).call().unwrap().into_json().unwrap();
+            if matches!(job.status, JobStatus::Success | JobStatus::Failure) {
+                return job;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("{} didn't finish", url);
+    }
+
+    #[test]
+    fn test_hosted_records_round_trip() {
+        let mut records = vec![
+            fixture_json("sync_proof.json"),
+            fixture_json("verify_proof.json"),
+        ];
+        records.extend(fixture_json("get_proofs.json").as_array().unwrap().clone());
+
+        for record in records {
+            let job: Job = serde_json::from_value(record.clone()).unwrap();
+            assert_eq!(job.proof_release, job.proof_request.release_id());
+            assert_eq!(json!(job), record);
+        }
+    }
+
+    #[test]
+    fn test_runner_decodes_requests() {
+        let store = store("prover-runner");
+        let dir = store.dir().to_path_buf();
+        let runner = ProverRunner::new(
+            FixtureRpc::load(fixtures()).unwrap(),
+            store,
+            Verifier::new(Network::Testnet.config()),
+        );
+
+        // Both kinds of request decode, and need their trusted head stored
+        for name in ["sync_proof.json", "verify_proof.json"] {
+            let request: ProofRequest =
+                serde_json::from_value(fixture_json(name)["proof_request"].clone()).unwrap();
+            let err = runner.run(request.input()).unwrap_err();
+            assert!(err.starts_with("trusted head"), "{}", err);
+        }
+        assert_eq!(
+            runner.run(&[0; 33]).unwrap_err(),
+            types::RequestError::Length(33).to_string()
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_runner_dry_runs_sync() {
+        let mut store = store("prover-runner-sync");
+        let mut checkpoint = Network::Testnet.checkpoint().unwrap();
+        // Shifted a seat, so no approval verifies and the block is never proven
+        checkpoint.epoch_bps.rotate_left(1);
+        store.commit(&checkpoint, None).unwrap();
+        let dir = store.dir().to_path_buf();
+        let runner = ProverRunner::new(
+            FixtureRpc::load(fixtures()).unwrap(),
+            store,
+            Verifier::new(Network::Testnet.config()),
+        );
+
+        assert_eq!(
+            runner.run(&checkpoint.head.hash()).unwrap_err(),
+            "next block doesn't sync: NotEnoughApprovedStake"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_serve_jobs() {
+        let service = ProverService::spawn(fake_runner, "127.0.0.1:0").unwrap();
+        let proofs = format!("{}/proofs", service.url());
+
+        let mut ids = Vec::new();
+        for name in ["sync_proof.json", "verify_proof.json"] {
+            let request = fixture_json(name)["proof_request"].clone();
+            let response = ureq::post(&proofs).send_json(request.clone()).unwrap();
+            assert_eq!(response.status(), 201);
+            let job: Job = response.into_json().unwrap();
+            assert_eq!(json!(job.proof_request), request);
+            ids.push(job.id);
+        }
+
+        let synced = wait_for(&format!("{}/{}", proofs, ids[0]));
+        assert_eq!(synced.status, JobStatus::Success);
+        assert_eq!(
+            synced.result.unwrap().proof,
+            synced.proof_request.input().to_vec()
+        );
+        let verified = wait_for(&format!("{}/{}", proofs, ids[1]));
+        assert_eq!(verified.status, JobStatus::Failure);
+        assert!(verified.error.is_some());
+
+        let listed: Vec<Job> = ureq::get(&proofs).call().unwrap().into_json().unwrap();
+        let listed: Vec<_> = listed.into_iter().map(|job| job.id).collect();
+        assert_eq!(listed, ids);
+
+        let err = ureq::get(&format!("{}/unknown", proofs))
+            .call()
+            .unwrap_err();
+        assert!(matches!(err, ureq::Error::Status(404, _)));
+        let err = ureq::post(&proofs)
+            .send_json(json!({ "type": "req_bytes", "releaseId": "", "data": { "input": "0xzz" } }))
+            .unwrap_err();
+        assert!(matches!(err, ureq::Error::Status(400, _)));
+    }
+}
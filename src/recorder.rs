@@ -0,0 +1,320 @@
+//! Records light client test vectors from an RPC into the
+//! `{ last_block_hash, body }` form of `fixtures/`, with a manifest of what was
+//! recorded. Every transition and proof is checked before it's written, so a
+//! recorded set is consistent by construction. A recording is staged next to
+//! its directory and only moved into place once complete, so a failed one
+//! leaves nothing behind.
+
+use crate::rpc::{base58, NearRpc, ProofId, RpcError};
+use crate::verifier::{InclusionInput, SyncInput, Verifier};
+use light_client_core::{BasicProof, Error, Hash, LightClientBlockView, TrustedState};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+use types::BlockHeight;
+
+pub const MANIFEST: &str = "manifest.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BlockEntry {
+    pub file: String,
+    /// The hash the block was requested for.
+    #[serde(with = "base58")]
+    pub last_block_hash: Hash,
+    #[serde(with = "base58")]
+    pub hash: Hash,
+    pub height: BlockHeight,
+    #[serde(with = "base58")]
+    pub epoch_id: Hash,
+    pub next_bps: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProofEntry {
+    pub file: String,
+    pub id: ProofId,
+    #[serde(with = "base58")]
+    pub light_client_head: Hash,
+    #[serde(with = "base58")]
+    pub block_hash: Hash,
+}
+
+/// What a recording holds. The first block is the checkpoint the rest sync
+/// from, and every proof is under the last.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub blocks: Vec<BlockEntry>,
+    pub proofs: Vec<ProofEntry>,
+}
+
+#[derive(Debug)]
+pub enum RecordError {
+    Rpc(RpcError),
+    Io(io::Error),
+    /// The node has no block past the `recorded`th.
+    NotEnoughBlocks {
+        recorded: usize,
+    },
+    /// Proofs were asked for with no block recorded for them to be under.
+    NoHead,
+    /// A recorded block or proof doesn't verify against the ones before it.
+    Inconsistent {
+        file: String,
+        error: Error,
+    },
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rpc(e) => write!(f, "{}", e),
+            Self::Io(e) => write!(f, "{}", e),
+            Self::NotEnoughBlocks { recorded } => {
+                write!(f, "node has no light client block past the {}th", recorded)
+            }
+            Self::NoHead => write!(f, "proofs need at least one block recorded"),
+            Self::Inconsistent { file, error } => write!(f, "{} doesn't verify: {:?}", file, error),
+        }
+    }
+}
+
+impl From<RpcError> for RecordError {
+    fn from(e: RpcError) -> Self {
+        Self::Rpc(e)
+    }
+}
+
+impl From<io::Error> for RecordError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+fn write_json(out: &Path, file: &str, value: &Value) -> io::Result<()> {
+    fs::write(out.join(file), serde_json::to_vec_pretty(value)?)
+}
+
+/// Record `epochs` consecutive light client blocks from `from` into `out`,
+/// then the proofs of `ids` under the last of them. `out` must not exist yet,
+/// or be empty.
+pub fn record(
+    rpc: &impl NearRpc,
+    verifier: &Verifier,
+    from: Hash,
+    epochs: usize,
+    ids: &[ProofId],
+    out: &Path,
+) -> Result<Manifest, RecordError> {
+    if epochs == 0 && !ids.is_empty() {
+        return Err(RecordError::NoHead);
+    }
+
+    let staging = staging_dir(out);
+    if staging.exists() {
+        // Left by a run that was killed
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+    let recorded = record_into(rpc, verifier, from, epochs, ids, &staging).and_then(|manifest| {
+        fs::rename(&staging, out)?;
+        Ok(manifest)
+    });
+    if recorded.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    recorded
+}
+
+/// A hidden sibling of `out`, so moving it into place is a rename on the same
+/// filesystem.
+fn staging_dir(out: &Path) -> PathBuf {
+    let name = out.file_name().unwrap_or_default().to_string_lossy();
+    out.with_file_name(format!(".{}.partial", name))
+}
+
+fn record_into(
+    rpc: &impl NearRpc,
+    verifier: &Verifier,
+    from: Hash,
+    epochs: usize,
+    ids: &[ProofId],
+    out: &Path,
+) -> Result<Manifest, RecordError> {
+    let mut manifest = Manifest {
+        blocks: Vec::new(),
+        proofs: Vec::new(),
+    };
+
+    let mut state: Option<TrustedState> = None;
+    let mut last_block_hash = from;
+    for i in 0..epochs {
+        let block = rpc
+            .next_light_client_block(&last_block_hash)?
+            .ok_or(RecordError::NotEnoughBlocks { recorded: i })?;
+        let file = format!("block_{}.json", i);
+        let inconsistent = |error| RecordError::Inconsistent {
+            file: file.clone(),
+            error,
+        };
+
+        // The first block is trusted, and each after must sync from the one before
+        if let Some(TrustedState { head, epoch_bps }) = state.take() {
+            verifier
+                .dry_run(SyncInput {
+                    head,
+                    epoch_bps,
+                    next_block: block.clone(),
+                })
+                .map_err(inconsistent)?;
+        }
+        let near_block = near_primitives::views::LightClientBlockView::try_from(block.clone())
+            .map_err(|e| RpcError::Decode(e.to_string()))?;
+        state = Some(
+            TrustedState::from_light_client_block(near_block.clone())
+                .ok_or_else(|| inconsistent(Error::NextBpsInvalid))?,
+        );
+
+        write_json(
+            out,
+            &file,
+            &json!({
+                "last_block_hash": near_primitives::hash::CryptoHash(last_block_hash),
+                "body": near_block,
+            }),
+        )?;
+        let hash = block.header().hash();
+        manifest
+            .blocks
+            .push(block_entry(file, last_block_hash, hash, &block));
+        last_block_hash = hash;
+    }
+
+    let head = state.map(|state| state.head);
+    for (i, id) in ids.iter().enumerate() {
+        let file = format!("proof_{}.json", i);
+        // Kept raw, so the file is exactly what the node returned
+        let result = rpc.call(
+            "EXPERIMENTAL_light_client_proof",
+            id.params(&last_block_hash),
+        )?;
+        let proof = BasicProof::from_rpc_json(&result.to_string())
+            .map_err(|e| RecordError::Rpc(RpcError::Decode(e.to_string())))?;
+        let block_hash = proof.outcome_proof.block_hash;
+        // Checked in `record`, so only reached with a recorded head
+        let head = head.as_ref().ok_or(RecordError::NoHead)?;
+        verifier
+            .dry_run(InclusionInput {
+                proof: (head.inner_lite.block_merkle_root, proof).into(),
+            })
+            .map_err(|error| RecordError::Inconsistent {
+                file: file.clone(),
+                error,
+            })?;
+
+        write_json(out, &file, &result)?;
+        manifest.proofs.push(ProofEntry {
+            file,
+            id: id.clone(),
+            light_client_head: last_block_hash,
+            block_hash,
+        });
+    }
+
+    write_json(out, MANIFEST, &json!(manifest))?;
+    Ok(manifest)
+}
+
+fn block_entry(
+    file: String,
+    last_block_hash: Hash,
+    hash: Hash,
+    block: &LightClientBlockView,
+) -> BlockEntry {
+    BlockEntry {
+        file,
+        last_block_hash,
+        hash,
+        height: block.inner_lite.height,
+        epoch_id: block.inner_lite.epoch_id,
+        next_bps: block.next_bps.as_ref().map_or(0, Vec::len),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::FixtureRpc;
+    use crate::test_util::{fixture, fixture_json, fixtures, temp_dir};
+    use light_client_core::Network;
+
+    #[test]
+    fn test_record_fixture_chain() {
+        let rpc = FixtureRpc::load(fixtures()).unwrap();
+        let verifier = Verifier::new(Network::Testnet.config());
+        let test_0 = fixture_json("test_0.json");
+        let from: near_primitives::hash::CryptoHash =
+            serde_json::from_value(test_0["last_block_hash"].clone()).unwrap();
+        let out = temp_dir("record");
+
+        let manifest = record(&rpc, &verifier, from.0, 3, &[], &out).unwrap();
+
+        let heights: Vec<_> = manifest.blocks.iter().map(|entry| entry.height).collect();
+        assert_eq!(heights, [154654776, 154697976, 154741176]);
+        // Recordings load as the fixtures they were recorded from
+        let recorded = FixtureRpc::load(&out).unwrap();
+        for entry in &manifest.blocks {
+            assert_eq!(
+                recorded
+                    .next_light_client_block(&entry.last_block_hash)
+                    .unwrap(),
+                rpc.next_light_client_block(&entry.last_block_hash).unwrap()
+            );
+        }
+        let read: Manifest =
+            serde_json::from_str(&fs::read_to_string(out.join(MANIFEST)).unwrap()).unwrap();
+        assert_eq!(read, manifest);
+
+        let err = record(&rpc, &verifier, from.0, 4, &[], &out).unwrap_err();
+        assert!(matches!(err, RecordError::NotEnoughBlocks { recorded: 3 }));
+        fs::remove_dir_all(out).unwrap();
+    }
+
+    #[test]
+    fn test_record_rejects_proof_not_under_head() {
+        let rpc = FixtureRpc::load(fixtures()).unwrap();
+        let verifier = Verifier::new(Network::Testnet.config());
+        let proof = crate::cli::read_proof(&fixture("new.json")).unwrap();
+        let id = ProofId::Receipt {
+            receipt_id: proof.outcome_proof.id,
+            receiver_id: proof.outcome_proof.outcome.executor_id,
+        };
+        let test_1 = crate::cli::read_block(&fixture("test_1.json")).unwrap();
+        let out = temp_dir("record-proof");
+
+        // The proof is of a block long before the recorded head
+        let err = record(&rpc, &verifier, test_1.header().hash(), 1, &[id], &out).unwrap_err();
+        assert!(matches!(err, RecordError::Inconsistent { file, .. } if file == "proof_0.json"));
+        // Nor are the blocks before it left behind
+        assert!(!out.exists());
+        assert!(!staging_dir(&out).exists());
+    }
+
+    #[test]
+    fn test_record_proofs_need_head() {
+        let rpc = FixtureRpc::load(fixtures()).unwrap();
+        let verifier = Verifier::new(Network::Testnet.config());
+        let proof = crate::cli::read_proof(&fixture("new.json")).unwrap();
+        let id = ProofId::Receipt {
+            receipt_id: proof.outcome_proof.id,
+            receiver_id: proof.outcome_proof.outcome.executor_id,
+        };
+        let out = temp_dir("record-no-head");
+
+        let err = record(&rpc, &verifier, Hash::default(), 0, &[id], &out).unwrap_err();
+        assert!(matches!(err, RecordError::NoHead));
+        assert!(!out.exists());
+    }
+}
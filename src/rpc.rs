@@ -0,0 +1,462 @@
+//! Typed access to the NEAR JSON-RPC methods the light client needs, either
+//! from a node over HTTP or from recorded responses like those in `fixtures/`.
+
+use light_client_core::{BasicProof, Hash, Header, LightClientBlockView};
+use near_primitives::hash::CryptoHash;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{collections::HashMap, fmt, fs, io, path::Path, time::Duration};
+use types::{AccountId, BlockHeight, OutcomeKind, OutcomeRequest};
+
+/// An execution outcome to prove inclusion of, in the form
+/// `EXPERIMENTAL_light_client_proof` takes it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProofId {
+    Transaction {
+        #[serde(with = "base58")]
+        transaction_hash: Hash,
+        sender_id: AccountId,
+    },
+    Receipt {
+        #[serde(with = "base58")]
+        receipt_id: Hash,
+        receiver_id: AccountId,
+    },
+}
+
+impl ProofId {
+    /// Id of the outcome, as in `outcome_proof.id` of its proof.
+    pub fn outcome_id(&self) -> &Hash {
+        match self {
+            Self::Transaction {
+                transaction_hash, ..
+            } => transaction_hash,
+            Self::Receipt { receipt_id, .. } => receipt_id,
+        }
+    }
+
+    /// Params of `EXPERIMENTAL_light_client_proof` for this outcome.
+    pub(crate) fn params(&self, light_client_head: &Hash) -> Value {
+        let mut params = json!(self);
+        params["light_client_head"] = json!(CryptoHash(*light_client_head));
+        params
+    }
+}
+
+impl From<OutcomeRequest> for ProofId {
+    fn from(request: OutcomeRequest) -> Self {
+        match request.kind {
+            OutcomeKind::Receipt => Self::Receipt {
+                receipt_id: request.id,
+                receiver_id: request.account_id,
+            },
+            OutcomeKind::Transaction => Self::Transaction {
+                transaction_hash: request.id,
+                sender_id: request.account_id,
+            },
+        }
+    }
+}
+
+/// Serde for hashes as base58 strings, as nearcore's RPC encodes them.
+pub(crate) mod base58 {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(hash: &Hash, serializer: S) -> Result<S::Ok, S::Error> {
+        CryptoHash(*hash).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Hash, D::Error> {
+        CryptoHash::deserialize(deserializer).map(|hash| hash.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockReference {
+    Final,
+    Hash(Hash),
+    Height(BlockHeight),
+}
+
+impl BlockReference {
+    fn params(&self) -> Value {
+        match self {
+            Self::Final => json!({ "finality": "final" }),
+            Self::Hash(hash) => json!({ "block_id": CryptoHash(*hash) }),
+            Self::Height(height) => json!({ "block_id": height }),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RpcError {
+    /// The node couldn't be reached or didn't answer in JSON-RPC.
+    Transport(String),
+    /// The node answered with a JSON-RPC error.
+    Rpc(Value),
+    /// The result isn't in the shape of the method's.
+    Decode(String),
+    /// The block, transaction or receipt asked for is unknown.
+    NotFound,
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(e) => write!(f, "rpc transport: {}", e),
+            Self::Rpc(e) => write!(f, "rpc error: {}", e),
+            Self::Decode(e) => write!(f, "malformed rpc result: {}", e),
+            Self::NotFound => write!(f, "not found"),
+        }
+    }
+}
+
+impl RpcError {
+    pub(crate) fn method_not_found(method: &str) -> Self {
+        Self::Rpc(json!({ "name": "METHOD_NOT_FOUND", "method": method }))
+    }
+}
+
+pub(crate) fn decode<T: serde::de::DeserializeOwned>(result: Value) -> Result<T, RpcError> {
+    serde_json::from_value(result).map_err(|e| RpcError::Decode(e.to_string()))
+}
+
+/// The `result` of a JSON-RPC response, or its `error`. nearcore reports
+/// unknown blocks, transactions and receipts as errors with an `UNKNOWN_`
+/// cause.
+fn result_of(mut response: Value) -> Result<Value, RpcError> {
+    if let Some(error) = response.get_mut("error") {
+        let unknown = error["cause"]["name"]
+            .as_str()
+            .is_some_and(|name| name.starts_with("UNKNOWN_"));
+        return Err(if unknown {
+            RpcError::NotFound
+        } else {
+            RpcError::Rpc(error.take())
+        });
+    }
+    response
+        .get_mut("result")
+        .map(Value::take)
+        .ok_or_else(|| RpcError::Transport("response has no result".into()))
+}
+
+/// The methods of a NEAR node's JSON-RPC the light client uses.
+pub trait NearRpc {
+    /// Call `method`, returning the `result` of the response.
+    fn call(&self, method: &str, params: Value) -> Result<Value, RpcError>;
+
+    /// The light client block of the epoch after `last_block_hash`'s, or
+    /// `None` if the node has none newer.
+    fn next_light_client_block(
+        &self,
+        last_block_hash: &Hash,
+    ) -> Result<Option<LightClientBlockView>, RpcError> {
+        let result = self.call(
+            "next_light_client_block",
+            json!({ "last_block_hash": CryptoHash(*last_block_hash) }),
+        )?;
+        // An empty result means the client is already at the node's head
+        if result.is_null() || result.as_object().is_some_and(|o| o.is_empty()) {
+            return Ok(None);
+        }
+        decode::<near_primitives::views::LightClientBlockView>(result)
+            .map(|block| Some(block.into()))
+    }
+
+    /// Proof of the outcome `id` under the block merkle root of
+    /// `light_client_head`.
+    fn light_client_proof(
+        &self,
+        id: &ProofId,
+        light_client_head: &Hash,
+    ) -> Result<BasicProof, RpcError> {
+        let result = self.call(
+            "EXPERIMENTAL_light_client_proof",
+            id.params(light_client_head),
+        )?;
+        BasicProof::from_rpc_json(&result.to_string()).map_err(|e| RpcError::Decode(e.to_string()))
+    }
+
+    fn block(&self, reference: BlockReference) -> Result<Header, RpcError> {
+        let block: near_primitives::views::BlockView =
+            decode(self.call("block", reference.params())?)?;
+        let header = near_primitives::block_header::BlockHeader::from(block.header);
+        Ok(near_primitives::views::LightClientBlockLiteView::from(header).into())
+    }
+}
+
+/// A node's JSON-RPC over HTTP.
+#[derive(Debug, Clone)]
+pub struct HttpRpc {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl HttpRpc {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .build(),
+        }
+    }
+
+    /// The public RPC of a preset network. Custom networks have none.
+    pub fn for_network(network: &light_client_core::Network) -> Option<Self> {
+        use light_client_core::Network;
+
+        match network {
+            Network::Mainnet => Some(Self::new("https://rpc.mainnet.near.org")),
+            Network::Testnet => Some(Self::new("https://rpc.testnet.near.org")),
+            Network::Localnet => Some(Self::new("http://localhost:3030")),
+            Network::Custom { .. } => None,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl NearRpc for HttpRpc {
+    fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": "near-lc-jolt",
+            "method": method,
+            "params": params,
+        });
+        // nearcore answers some errors with a non-200 status, still in JSON-RPC
+        let response = match self.agent.post(&self.url).send_json(request) {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => return Err(RpcError::Transport(e.to_string())),
+        };
+        result_of(
+            response
+                .into_json()
+                .map_err(|e| RpcError::Transport(e.to_string()))?,
+        )
+    }
+}
+
+/// Recorded RPC responses, as found in `fixtures/`.
+///
+/// Light client blocks are served by the `last_block_hash` they were recorded
+/// for, or else to any block of the epoch before theirs whose header is known.
+/// Proofs are served by outcome id, whatever the light client head asked for.
+#[derive(Debug, Clone, Default)]
+pub struct FixtureRpc {
+    blocks: Vec<near_primitives::views::LightClientBlockView>,
+    by_last_block_hash: HashMap<Hash, usize>,
+    proofs: HashMap<Hash, Value>,
+    headers: HashMap<Hash, Header>,
+}
+
+impl FixtureRpc {
+    /// Load every recognized `.json` file in `dir`: `{ last_block_hash, body }`
+    /// blocks, RPC proofs, bare headers and JSON-RPC envelopes of any of them.
+    /// Others are skipped.
+    pub fn load(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let mut paths = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        paths.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+        paths.sort();
+
+        let mut rpc = Self::default();
+        for path in paths {
+            let value: Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+            rpc.insert(value).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", path.display(), e),
+                )
+            })?;
+        }
+        Ok(rpc)
+    }
+
+    /// Add a recorded response, returning whether it was recognized.
+    pub fn insert(&mut self, mut value: Value) -> Result<bool, serde_json::Error> {
+        if value.get("jsonrpc").is_some() {
+            let Some(result) = value.get_mut("result") else {
+                return Ok(false);
+            };
+            value = result.take();
+        }
+
+        if let (Some(last_block_hash), Some(body)) =
+            (value.get("last_block_hash"), value.get("body"))
+        {
+            let last_block_hash: CryptoHash = serde_json::from_value(last_block_hash.clone())?;
+            self.insert_block(
+                Some(last_block_hash.0),
+                serde_json::from_value(body.clone())?,
+            );
+        } else if value.get("approvals_after_next").is_some() {
+            self.insert_block(None, serde_json::from_value(value)?);
+        } else if let Some(outcome_proof) = value.get("outcome_proof") {
+            let id: CryptoHash = serde_json::from_value(outcome_proof["id"].clone())?;
+            self.insert_header(serde_json::from_value(value["block_header_lite"].clone())?);
+            self.proofs.insert(id.0, value);
+        } else if value.get("inner_lite").is_some() && value.get("prev_block_hash").is_some() {
+            self.insert_header(serde_json::from_value(value)?);
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn insert_block(
+        &mut self,
+        last_block_hash: Option<Hash>,
+        block: near_primitives::views::LightClientBlockView,
+    ) {
+        self.insert_header(near_primitives::views::LightClientBlockLiteView {
+            prev_block_hash: block.prev_block_hash,
+            inner_rest_hash: block.inner_rest_hash,
+            inner_lite: block.inner_lite.clone(),
+        });
+        if let Some(last_block_hash) = last_block_hash {
+            self.by_last_block_hash
+                .insert(last_block_hash, self.blocks.len());
+        }
+        self.blocks.push(block);
+    }
+
+    fn insert_header(&mut self, header: near_primitives::views::LightClientBlockLiteView) {
+        let header = Header::from(header);
+        self.headers.insert(header.hash(), header);
+    }
+
+    fn next_block(&self, last_block_hash: &Hash) -> Result<Value, RpcError> {
+        if let Some(&i) = self.by_last_block_hash.get(last_block_hash) {
+            return Ok(json!(self.blocks[i]));
+        }
+        let head = self
+            .headers
+            .get(last_block_hash)
+            .ok_or(RpcError::NotFound)?;
+        let next_epoch_id = CryptoHash(head.inner_lite.next_epoch_id);
+        Ok(self
+            .blocks
+            .iter()
+            .filter(|block| block.inner_lite.epoch_id == next_epoch_id)
+            .max_by_key(|block| block.inner_lite.height)
+            .map_or_else(|| json!({}), |block| json!(block)))
+    }
+}
+
+impl NearRpc for FixtureRpc {
+    fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "next_light_client_block" => {
+                let last_block_hash: CryptoHash = decode(params["last_block_hash"].clone())?;
+                self.next_block(&last_block_hash.0)
+            }
+            "EXPERIMENTAL_light_client_proof" => {
+                let id: ProofId = decode(params)?;
+                self.proofs
+                    .get(id.outcome_id())
+                    .cloned()
+                    .ok_or(RpcError::NotFound)
+            }
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+
+    /// Fixtures hold headers rather than full blocks, so these are served
+    /// directly. The final block is the highest known.
+    fn block(&self, reference: BlockReference) -> Result<Header, RpcError> {
+        let header = match reference {
+            BlockReference::Final => self
+                .headers
+                .values()
+                .max_by_key(|header| header.inner_lite.height),
+            BlockReference::Hash(hash) => self.headers.get(&hash),
+            BlockReference::Height(height) => self
+                .headers
+                .values()
+                .find(|header| header.inner_lite.height == height),
+        };
+        header.cloned().ok_or(RpcError::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{read_block, read_proof};
+    use crate::test_util::{fixture, fixtures};
+
+    #[test]
+    fn test_fixture_light_client_blocks() {
+        let rpc = FixtureRpc::load(fixtures()).unwrap();
+        let test_0 = read_block(&fixture("test_0.json")).unwrap();
+        let test_1 = read_block(&fixture("test_1.json")).unwrap();
+
+        let test_2 = read_block(&fixture("test_2.json")).unwrap();
+
+        // From a block of the epoch before, and by the hash recorded for it
+        let next = rpc
+            .next_light_client_block(&test_0.header().hash())
+            .unwrap();
+        assert_eq!(next, Some(test_1.clone()));
+        let next = rpc
+            .next_light_client_block(&test_1.header().hash())
+            .unwrap();
+        assert_eq!(next, Some(test_2.clone()));
+        // Nothing is recorded past the last block
+        let next = rpc
+            .next_light_client_block(&test_2.header().hash())
+            .unwrap();
+        assert_eq!(next, None);
+
+        let head = rpc.block(BlockReference::Hash(test_0.header().hash()));
+        assert_eq!(head.unwrap().hash(), test_0.header().hash());
+
+        let unknown = rpc.next_light_client_block(&[0; 32]);
+        assert!(matches!(unknown, Err(RpcError::NotFound)));
+    }
+
+    #[test]
+    fn test_fixture_proofs() {
+        let rpc = FixtureRpc::load(fixtures()).unwrap();
+        let expected = read_proof(&fixture("new.json")).unwrap();
+        let id = ProofId::Receipt {
+            receipt_id: expected.outcome_proof.id,
+            receiver_id: expected.outcome_proof.outcome.executor_id.clone(),
+        };
+
+        let proof = rpc.light_client_proof(&id, &[0; 32]).unwrap();
+        assert_eq!(proof.outcome_proof, expected.outcome_proof);
+        assert_eq!(proof.block_header_lite, expected.block_header_lite);
+    }
+
+    #[test]
+    fn test_proof_ids() {
+        let ids: Vec<ProofId> =
+            serde_json::from_str(&fs::read_to_string(fixture("ids.json")).unwrap()).unwrap();
+        assert!(matches!(ids[1], ProofId::Transaction { .. }));
+
+        let json = serde_json::to_value(&ids).unwrap();
+        assert_eq!(serde_json::from_value::<Vec<ProofId>>(json).unwrap(), ids);
+    }
+
+    #[test]
+    fn test_result_of() {
+        assert_eq!(
+            result_of(json!({ "jsonrpc": "2.0", "result": 1 })).unwrap(),
+            json!(1)
+        );
+        let unknown =
+            json!({ "error": { "name": "HANDLER_ERROR", "cause": { "name": "UNKNOWN_BLOCK" } } });
+        assert!(matches!(result_of(unknown), Err(RpcError::NotFound)));
+        let other = json!({ "error": { "name": "INTERNAL_ERROR" } });
+        assert!(matches!(result_of(other), Err(RpcError::Rpc(_))));
+    }
+}
@@ -0,0 +1,78 @@
+//! A JSON-over-HTTP server answering from a background thread until dropped,
+//! as the stand-in RPC and the prover service are.
+
+use serde_json::{json, Value};
+use std::{
+    io::{self, Read},
+    net::SocketAddr,
+    sync::Arc,
+    thread::{self, JoinHandle},
+};
+
+pub struct JsonServer {
+    server: Arc<tiny_http::Server>,
+    addr: SocketAddr,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl JsonServer {
+    /// Serve on `addr`, e.g. `127.0.0.1:0` for any free port, answering each
+    /// request with the status and body `handler` gives for its method, url
+    /// and body.
+    pub fn spawn(
+        addr: &str,
+        handler: impl Fn(&str, &str, &[u8]) -> (u16, Value) + Send + 'static,
+    ) -> io::Result<Self> {
+        let server = tiny_http::Server::http(addr)
+            .map(Arc::new)
+            .map_err(|e| io::Error::new(io::ErrorKind::AddrNotAvailable, e))?;
+        let addr = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "not an ip address"))?;
+
+        let handle = thread::spawn({
+            let server = server.clone();
+            move || {
+                let content_type =
+                    tiny_http::Header::from_bytes("Content-Type", "application/json").unwrap();
+                for mut request in server.incoming_requests() {
+                    let mut body = Vec::new();
+                    let (status, response) = match request.as_reader().read_to_end(&mut body) {
+                        Ok(_) => handler(request.method().as_str(), request.url(), &body),
+                        Err(e) => (400, json!({ "error": e.to_string() })),
+                    };
+                    let response = tiny_http::Response::from_string(response.to_string())
+                        .with_status_code(status)
+                        .with_header(content_type.clone());
+                    // The client may have hung up, which is its problem
+                    let _ = request.respond(response);
+                }
+            }
+        });
+
+        Ok(Self {
+            server,
+            addr,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Serve until the process is killed.
+    pub fn wait(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for JsonServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        self.wait();
+    }
+}
@@ -0,0 +1,225 @@
+//! A stand-in for a NEAR node's JSON-RPC, serving recorded responses or a
+//! synthetic chain over HTTP, so `HttpRpc` can be tested as it runs against
+//! a real node without network access.
+
+use crate::rpc::{decode, BlockReference, NearRpc, RpcError};
+use crate::server::JsonServer;
+use chain_generator::Chain;
+use light_client_core::{Hash, Header};
+use near_primitives::hash::CryptoHash;
+use serde_json::{json, Value};
+use std::{collections::HashMap, io};
+use types::BlockHeight;
+
+/// Serves the light client blocks of a generated chain. The chain has no
+/// transactions, so no outcome can be proven.
+#[derive(Debug, Clone)]
+pub struct ChainRpc {
+    chain: Chain,
+    heights: HashMap<Hash, BlockHeight>,
+}
+
+impl ChainRpc {
+    pub fn new(chain: Chain) -> Self {
+        let heights = chain
+            .headers
+            .iter()
+            .map(|header| (header.hash(), header.inner_lite.height))
+            .collect();
+        Self { chain, heights }
+    }
+
+    pub fn chain(&self) -> &Chain {
+        &self.chain
+    }
+}
+
+impl NearRpc for ChainRpc {
+    fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "next_light_client_block" => {
+                let last_block_hash: CryptoHash = decode(params["last_block_hash"].clone())?;
+                let height = self
+                    .heights
+                    .get(&last_block_hash.0)
+                    .ok_or(RpcError::NotFound)?;
+                // `light_client_blocks[k]` is of epoch `k + 1`, the one after `height`'s
+                let epoch = (height / self.chain.config.epoch_length) as usize;
+                let Some(block) = self.chain.light_client_blocks.get(epoch) else {
+                    return Ok(json!({}));
+                };
+                let block = near_primitives::views::LightClientBlockView::try_from(block.clone())
+                    .map_err(|e| RpcError::Transport(format!("generated block: {}", e)))?;
+                Ok(json!(block))
+            }
+            "EXPERIMENTAL_light_client_proof" => Err(RpcError::NotFound),
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+
+    fn block(&self, reference: BlockReference) -> Result<Header, RpcError> {
+        let height = match reference {
+            BlockReference::Final => self.chain.headers.len() as BlockHeight - 1,
+            BlockReference::Hash(hash) => *self.heights.get(&hash).ok_or(RpcError::NotFound)?,
+            BlockReference::Height(height) => height,
+        };
+        self.chain
+            .headers
+            .get(height as usize)
+            .cloned()
+            .ok_or(RpcError::NotFound)
+    }
+}
+
+/// The error nearcore answers `method` with for `error`.
+fn error_json(method: &str, error: RpcError) -> Value {
+    let error_message = error.to_string();
+    match error {
+        RpcError::NotFound if method.ends_with("light_client_proof") => json!({
+            "name": "HANDLER_ERROR",
+            "cause": { "name": "UNKNOWN_TRANSACTION_OR_RECEIPT" },
+        }),
+        RpcError::NotFound => json!({
+            "name": "HANDLER_ERROR",
+            "cause": { "name": "UNKNOWN_BLOCK" },
+        }),
+        RpcError::Rpc(error) => error,
+        RpcError::Decode(_) => json!({
+            "name": "REQUEST_VALIDATION_ERROR",
+            "cause": { "name": "PARSE_ERROR", "info": { "error_message": error_message } },
+        }),
+        RpcError::Transport(_) => json!({
+            "name": "INTERNAL_ERROR",
+            "cause": { "name": "INTERNAL_ERROR", "info": { "error_message": error_message } },
+        }),
+    }
+}
+
+/// Answer a JSON-RPC request from `rpc`.
+pub fn respond(rpc: &impl NearRpc, request: &[u8]) -> Value {
+    let Ok(request) = serde_json::from_slice::<Value>(request) else {
+        return json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": { "name": "REQUEST_VALIDATION_ERROR", "cause": { "name": "PARSE_ERROR" } },
+        });
+    };
+    let id = request["id"].clone();
+    let method = match request["method"].as_str().unwrap_or_default() {
+        // Stabilized under this name in later nearcore
+        "light_client_proof" => "EXPERIMENTAL_light_client_proof",
+        method => method,
+    };
+
+    match rpc.call(method, request["params"].clone()) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => json!({ "jsonrpc": "2.0", "id": id, "error": error_json(method, e) }),
+    }
+}
+
+/// A stand-in RPC server answering from a background thread until dropped.
+pub struct StandIn {
+    server: JsonServer,
+}
+
+impl StandIn {
+    /// Serve `rpc` on `addr`, e.g. `127.0.0.1:0` for any free port.
+    pub fn spawn(rpc: impl NearRpc + Send + 'static, addr: &str) -> io::Result<Self> {
+        // JSON-RPC answers errors in the body, so every response is a 200
+        let server = JsonServer::spawn(addr, move |_, _, body| (200, respond(&rpc, body)))?;
+        Ok(Self { server })
+    }
+
+    pub fn url(&self) -> String {
+        self.server.url()
+    }
+
+    /// Serve until the process is killed.
+    pub fn wait(mut self) {
+        self.server.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{read_block, read_proof};
+    use crate::rpc::{FixtureRpc, HttpRpc, ProofId};
+    use crate::test_util::{fixture, fixtures};
+    use chain_generator::ChainConfig;
+    use light_client_core::Protocol;
+    use types::ValidatorStakeView;
+
+    #[test]
+    fn test_sync_generated_chain_over_http() {
+        let chain = Chain::generate(ChainConfig {
+            rotation: 1,
+            ..Default::default()
+        });
+        let stand_in = StandIn::spawn(ChainRpc::new(chain.clone()), "127.0.0.1:0").unwrap();
+        let rpc = HttpRpc::new(stand_in.url());
+
+        let mut state = chain.genesis_state();
+        let mut synced = 0;
+        while let Some(block) = rpc.next_light_client_block(&state.head.hash()).unwrap() {
+            state = Protocol::sync_many(state, vec![block]).unwrap();
+            synced += 1;
+        }
+
+        assert_eq!(synced, chain.config.epochs);
+        assert_eq!(
+            state.head.hash(),
+            chain.light_client_blocks.last().unwrap().header().hash()
+        );
+        assert!(matches!(
+            rpc.next_light_client_block(&[0; 32]),
+            Err(RpcError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_account_id_is_an_error() {
+        let mut chain = Chain::generate(ChainConfig::default());
+        let ValidatorStakeView::V1(bp) =
+            &mut chain.light_client_blocks[0].next_bps.as_mut().unwrap()[0];
+        bp.account_id = "Not An Account".into();
+        let rpc = ChainRpc::new(chain.clone());
+
+        assert!(matches!(
+            rpc.call(
+                "next_light_client_block",
+                json!({ "last_block_hash": CryptoHash(chain.headers[0].hash()) })
+            ),
+            Err(RpcError::Transport(_))
+        ));
+    }
+
+    #[test]
+    fn test_serve_fixtures_over_http() {
+        let stand_in =
+            StandIn::spawn(FixtureRpc::load(fixtures()).unwrap(), "127.0.0.1:0").unwrap();
+        let rpc = HttpRpc::new(stand_in.url());
+
+        let test_0 = read_block(&fixture("test_0.json")).unwrap();
+        let test_1 = read_block(&fixture("test_1.json")).unwrap();
+        let next = rpc.next_light_client_block(&test_0.header().hash());
+        assert_eq!(next.unwrap(), Some(test_1));
+
+        let expected = read_proof(&fixture("old.json")).unwrap();
+        let id = ProofId::Transaction {
+            transaction_hash: expected.outcome_proof.id,
+            sender_id: expected.outcome_proof.outcome.executor_id.clone(),
+        };
+        let proof = rpc.light_client_proof(&id, &[0; 32]).unwrap();
+        assert_eq!(proof.outcome_proof, expected.outcome_proof);
+
+        let unknown = ProofId::Receipt {
+            receipt_id: [0; 32],
+            receiver_id: "nobody.testnet".into(),
+        };
+        assert!(matches!(
+            rpc.light_client_proof(&unknown, &[0; 32]),
+            Err(RpcError::NotFound)
+        ));
+    }
+}
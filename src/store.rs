@@ -0,0 +1,330 @@
+//! Persistent light client state: every verified head, the block producers of
+//! each epoch, the proof artifact that justified each head and a pointer to
+//! the latest, so the host resumes where it stopped rather than from a
+//! checkpoint.
+
+use crate::artifact::{ArtifactError, ProofArtifact, PublicIo};
+use crate::verifier::SyncInput;
+use light_client_core::{Hash, Header, TrustedState, ValidatorStake};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+use types::{bps_hash, EpochId};
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(io::Error),
+    /// The store's contents are inconsistent with each other.
+    Corrupt(String),
+    /// The artifact justifying the latest head doesn't verify.
+    Artifact(ArtifactError),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Corrupt(e) => write!(f, "corrupt store: {}", e),
+            Self::Artifact(e) => write!(f, "latest head's proof: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for StoreError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+pub trait HeaderStore {
+    /// Store `state` and the artifact proving the sync to its head, then make
+    /// it the latest head. A head stored without an artifact is trusted as
+    /// given, as a checkpoint is.
+    fn commit(
+        &mut self,
+        state: &TrustedState,
+        artifact: Option<&ProofArtifact>,
+    ) -> Result<(), StoreError>;
+
+    fn head(&self, hash: &Hash) -> Result<Option<Header>, StoreError>;
+
+    /// Block producers of the epoch `epoch_id`.
+    fn bps(&self, epoch_id: &EpochId) -> Result<Option<Vec<ValidatorStake>>, StoreError>;
+
+    fn artifact(&self, hash: &Hash) -> Result<Option<ProofArtifact>, StoreError>;
+
+    fn latest_hash(&self) -> Result<Option<Hash>, StoreError>;
+
+    /// The latest head, with the block producers of the epoch after it.
+    fn latest(&self) -> Result<Option<TrustedState>, StoreError> {
+        let Some(hash) = self.latest_hash()? else {
+            return Ok(None);
+        };
+        let head = self.head(&hash)?.ok_or_else(|| {
+            StoreError::Corrupt(format!("latest head {} is missing", hex::encode(hash)))
+        })?;
+        let epoch_bps = self.bps(&head.inner_lite.next_epoch_id)?.ok_or_else(|| {
+            StoreError::Corrupt(format!(
+                "block producers of epoch {} are missing",
+                hex::encode(head.inner_lite.next_epoch_id)
+            ))
+        })?;
+        Ok(Some(TrustedState::new(head, epoch_bps)))
+    }
+
+    /// The latest state, once the chain tip is re-verified: the head is the
+    /// one pointed to, its block producers are the ones it commits to, and
+    /// its artifact, if any, is a valid proof of syncing to it from a stored
+    /// head.
+    fn recover(&self) -> Result<Option<TrustedState>, StoreError> {
+        let Some(state) = self.latest()? else {
+            return Ok(None);
+        };
+        let hash = state.head.hash();
+        if Some(hash) != self.latest_hash()? {
+            return Err(StoreError::Corrupt(
+                "latest head doesn't hash to its key".into(),
+            ));
+        }
+        if bps_hash(&state.epoch_bps) != state.head.inner_lite.next_bp_hash {
+            return Err(StoreError::Corrupt(
+                "block producers don't match the latest head's next_bp_hash".into(),
+            ));
+        }
+
+        if let Some(artifact) = self.artifact(&hash)? {
+            let PublicIo::SyncLc { input, output } = &artifact.io else {
+                return Err(StoreError::Corrupt(
+                    "latest head's artifact is not of a sync".into(),
+                ));
+            };
+            if input.next_state(output) != state {
+                return Err(StoreError::Corrupt(
+                    "latest head's artifact proves a different state".into(),
+                ));
+            }
+            ensure_synced_from_store(self, input)?;
+            artifact.verify().map_err(StoreError::Artifact)?;
+        }
+        Ok(Some(state))
+    }
+}
+
+/// The proof only shows `input.next_block` follows from `input.head` and
+/// `input.epoch_bps`, so check those are a stored head and the block
+/// producers it commits to.
+fn ensure_synced_from_store<S: HeaderStore + ?Sized>(
+    store: &S,
+    input: &SyncInput,
+) -> Result<(), StoreError> {
+    let head = &input.head;
+    if store.head(&head.hash())?.is_none() {
+        return Err(StoreError::Corrupt(
+            "latest head was synced from a head not in the store".into(),
+        ));
+    }
+    if bps_hash(&input.epoch_bps) != head.inner_lite.next_bp_hash {
+        return Err(StoreError::Corrupt(
+            "latest head was synced with block producers its previous head doesn't commit to"
+                .into(),
+        ));
+    }
+    if store.bps(&head.inner_lite.next_epoch_id)?.as_ref() != Some(&input.epoch_bps) {
+        return Err(StoreError::Corrupt(
+            "latest head was synced with block producers other than the stored ones".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// A `HeaderStore` in a directory, one file per entry:
+///
+/// ```text
+/// heads/<hash>.json
+/// bps/<epoch id>.json
+/// artifacts/<hash>.nlcj
+/// LATEST
+/// ```
+///
+/// Each file is written to a temporary one and renamed into place, and
+/// `LATEST` only once everything it points to is, so an interrupted commit
+/// leaves the previous head latest.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        for sub in ["heads", "bps", "artifacts"] {
+            fs::create_dir_all(dir.join(sub))?;
+        }
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, kind: &str, key: &Hash, extension: &str) -> PathBuf {
+        self.dir
+            .join(kind)
+            .join(format!("{}.{}", hex::encode(key), extension))
+    }
+
+    fn write(
+        &self,
+        path: &Path,
+        write: impl FnOnce(&mut fs::File) -> Result<(), StoreError>,
+    ) -> Result<(), StoreError> {
+        let tmp = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp)?;
+        write(&mut file)?;
+        file.sync_all()?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    fn write_json(&self, path: &Path, value: &impl serde::Serialize) -> Result<(), StoreError> {
+        self.write(path, |file| {
+            serde_json::to_writer(file, value).map_err(|e| StoreError::Io(e.into()))
+        })
+    }
+
+    fn read_json<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &Path,
+    ) -> Result<Option<T>, StoreError> {
+        match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| StoreError::Corrupt(format!("{}: {}", path.display(), e))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl HeaderStore for FileStore {
+    fn commit(
+        &mut self,
+        state: &TrustedState,
+        artifact: Option<&ProofArtifact>,
+    ) -> Result<(), StoreError> {
+        let hash = state.head.hash();
+        if let Some(artifact) = artifact {
+            self.write(&self.path("artifacts", &hash, "nlcj"), |file| {
+                let mut writer = io::BufWriter::new(file);
+                artifact
+                    .write_to(&mut writer)
+                    .map_err(StoreError::Artifact)?;
+                Ok(io::Write::flush(&mut writer)?)
+            })?;
+        }
+        self.write_json(
+            &self.path("bps", &state.head.inner_lite.next_epoch_id, "json"),
+            &state.epoch_bps,
+        )?;
+        self.write_json(&self.path("heads", &hash, "json"), &state.head)?;
+        self.write(&self.dir.join("LATEST"), |file| {
+            io::Write::write_all(file, hex::encode(hash).as_bytes()).map_err(Into::into)
+        })
+    }
+
+    fn head(&self, hash: &Hash) -> Result<Option<Header>, StoreError> {
+        self.read_json(&self.path("heads", hash, "json"))
+    }
+
+    fn bps(&self, epoch_id: &EpochId) -> Result<Option<Vec<ValidatorStake>>, StoreError> {
+        self.read_json(&self.path("bps", epoch_id, "json"))
+    }
+
+    fn artifact(&self, hash: &Hash) -> Result<Option<ProofArtifact>, StoreError> {
+        match ProofArtifact::read(self.path("artifacts", hash, "nlcj")) {
+            Ok(artifact) => Ok(Some(artifact)),
+            Err(ArtifactError::Io(e)) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(StoreError::Artifact(e)),
+        }
+    }
+
+    fn latest_hash(&self) -> Result<Option<Hash>, StoreError> {
+        let latest = match fs::read_to_string(self.dir.join("LATEST")) {
+            Ok(latest) => latest,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        hex::decode(latest.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(Some)
+            .ok_or_else(|| StoreError::Corrupt(format!("LATEST is not a hash: {}", latest)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{store, sync_input};
+    use light_client_core::Network;
+
+    #[test]
+    fn test_recover_latest() {
+        let mut store = store("store");
+        assert_eq!(store.recover().unwrap(), None);
+
+        let checkpoint = Network::Testnet.checkpoint().unwrap();
+        store.commit(&checkpoint, None).unwrap();
+        assert_eq!(store.recover().unwrap(), Some(checkpoint.clone()));
+
+        // Reopened, as on restart
+        let store = FileStore::open(store.dir()).unwrap();
+        assert_eq!(store.recover().unwrap(), Some(checkpoint));
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_recover_detects_corruption() {
+        let mut store = store("store-corrupt");
+        let mut checkpoint = Network::Testnet.checkpoint().unwrap();
+        checkpoint.epoch_bps.pop();
+        store.commit(&checkpoint, None).unwrap();
+        assert!(matches!(store.recover(), Err(StoreError::Corrupt(_))));
+
+        fs::write(store.dir().join("LATEST"), hex::encode([1; 32])).unwrap();
+        assert!(matches!(store.recover(), Err(StoreError::Corrupt(_))));
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_sync_input_checked_against_store() {
+        let mut store = store("store-sync-input");
+        let input = sync_input();
+        let checkpoint = TrustedState::new(input.head.clone(), input.epoch_bps.clone());
+        assert!(matches!(
+            ensure_synced_from_store(&store, &input),
+            Err(StoreError::Corrupt(_))
+        ));
+
+        store.commit(&checkpoint, None).unwrap();
+        ensure_synced_from_store(&store, &input).unwrap();
+
+        let mut uncommitted = input.clone();
+        uncommitted.epoch_bps.pop();
+        assert!(matches!(
+            ensure_synced_from_store(&store, &uncommitted),
+            Err(StoreError::Corrupt(_))
+        ));
+
+        // Committed to by the head, but not the ones stored for it
+        let mut stored = checkpoint.clone();
+        stored.epoch_bps.pop();
+        store.commit(&stored, None).unwrap();
+        assert!(matches!(
+            ensure_synced_from_store(&store, &input),
+            Err(StoreError::Corrupt(_))
+        ));
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+}
@@ -0,0 +1,46 @@
+//! Fixtures and scratch space shared by the tests.
+
+use crate::cli::read_block;
+use crate::store::FileStore;
+use crate::verifier::SyncInput;
+use light_client_core::{Network, TrustedState};
+use serde_json::Value;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures")
+}
+
+pub fn fixture(name: &str) -> PathBuf {
+    fixtures().join(name)
+}
+
+pub fn fixture_json(name: &str) -> Value {
+    serde_json::from_str(&fs::read_to_string(fixture(name)).unwrap()).unwrap()
+}
+
+/// A path under the temp dir for a test to write to, unique to `name` and
+/// this process, with nothing left there from a previous run.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("near-lc-jolt-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+/// An empty `FileStore` in `temp_dir(name)`.
+pub fn store(name: &str) -> FileStore {
+    FileStore::open(temp_dir(name)).unwrap()
+}
+
+/// The testnet checkpoint, with the recorded block after it to sync to.
+pub fn sync_input() -> SyncInput {
+    let TrustedState { head, epoch_bps } = Network::Testnet.checkpoint().unwrap();
+    SyncInput {
+        head,
+        epoch_bps,
+        next_block: read_block(&fixture("test_1.json")).unwrap(),
+    }
+}
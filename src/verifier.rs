@@ -0,0 +1,282 @@
+use light_client_core::{
+    BasicProof, BlockHeaderInnerRest, Error, Hash, Header, LcProof, LightClientBlockView,
+    MerklePath, Predicate, PredicateOutput, Protocol, ProtocolConfig, RandomBeacon, SyncOutput,
+    SyncedOutcome, TrustedState, ValidatorStake, VerifiedInnerRest, VerifiedOutcome,
+};
+use serde::{Deserialize, Serialize};
+
+/// Inputs to a guest entrypoint that can also be checked natively.
+pub trait DryRun {
+    type Output;
+
+    fn dry_run(self, config: &ProtocolConfig) -> Result<Self::Output, Error>;
+}
+
+/// Bytes a guest deserializes `input` from.
+pub fn input_size<T: Serialize>(input: &T) -> usize {
+    postcard::to_stdvec(input).unwrap().len()
+}
+
+/// Inputs to `sync_lc`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncInput {
+    pub head: Header,
+    pub epoch_bps: Vec<ValidatorStake>,
+    pub next_block: LightClientBlockView,
+}
+
+impl SyncInput {
+    /// The state after syncing to `next_block`, given the output of doing so:
+    /// its header, and the next block producers it carries, if any.
+    pub fn next_state(&self, output: &SyncOutput) -> TrustedState {
+        let epoch_bps = match &self.next_block.next_bps {
+            Some(next_bps) => next_bps.iter().cloned().map(Into::into).collect(),
+            None => self.epoch_bps.clone(),
+        };
+        TrustedState::new(output.new_head.clone(), epoch_bps)
+    }
+}
+
+impl DryRun for SyncInput {
+    type Output = SyncOutput;
+
+    fn dry_run(self, config: &ProtocolConfig) -> Result<Self::Output, Error> {
+        Protocol::sync_with_config(&self.head, &self.epoch_bps, self.next_block, config)
+            .map(Into::into)
+    }
+}
+
+/// Inputs to `verify_inner_rest`: a sync, and the preimage of the new head's
+/// `inner_rest_hash`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InnerRestInput {
+    pub sync: SyncInput,
+    pub inner_rest: BlockHeaderInnerRest,
+}
+
+impl DryRun for InnerRestInput {
+    type Output = VerifiedInnerRest;
+
+    fn dry_run(self, _: &ProtocolConfig) -> Result<Self::Output, Error> {
+        let SyncInput {
+            head,
+            epoch_bps,
+            next_block,
+        } = self.sync;
+        let new_head = Protocol::sync(&head, &epoch_bps, next_block)?.new_head;
+        Protocol::ensure_inner_rest_matches(&new_head, &self.inner_rest)?;
+        Ok(VerifiedInnerRest::new(new_head.hash(), &self.inner_rest))
+    }
+}
+
+/// Inputs to `random_beacon`: a sync, and a block proven against the new head
+/// with the preimage of its `inner_rest_hash`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeaconInput {
+    pub sync: SyncInput,
+    pub target: Header,
+    pub block_proof: MerklePath,
+    pub inner_rest: BlockHeaderInnerRest,
+}
+
+impl DryRun for BeaconInput {
+    type Output = RandomBeacon;
+
+    fn dry_run(self, _: &ProtocolConfig) -> Result<Self::Output, Error> {
+        let SyncInput {
+            head,
+            epoch_bps,
+            next_block,
+        } = self.sync;
+        let new_head = Protocol::sync(&head, &epoch_bps, next_block)?.new_head;
+        Protocol::verify_random_value(&new_head, &self.target, &self.block_proof, &self.inner_rest)
+    }
+}
+
+/// Inputs to `inclusion`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InclusionInput {
+    pub proof: LcProof,
+}
+
+impl DryRun for InclusionInput {
+    type Output = VerifiedOutcome;
+
+    fn dry_run(self, config: &ProtocolConfig) -> Result<Self::Output, Error> {
+        Protocol::verify_inclusion(self.proof, config.num_shards)
+    }
+}
+
+/// Inputs to `inclusion_predicate`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PredicateInput {
+    pub proof: LcProof,
+    pub predicate: Predicate,
+}
+
+impl DryRun for PredicateInput {
+    type Output = PredicateOutput;
+
+    fn dry_run(self, _: &ProtocolConfig) -> Result<Self::Output, Error> {
+        Protocol::inclusion_proof_verify_with(self.proof, &self.predicate)
+    }
+}
+
+/// Inputs to `sync_and_include`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncAndIncludeInput {
+    pub state: TrustedState,
+    pub blocks: Vec<LightClientBlockView>,
+    pub proof: LcProof,
+}
+
+impl DryRun for SyncAndIncludeInput {
+    type Output = SyncedOutcome;
+
+    fn dry_run(self, config: &ProtocolConfig) -> Result<Self::Output, Error> {
+        Protocol::sync_and_include(self.state, self.blocks, self.proof, config.num_shards)
+    }
+}
+
+/// Inputs to `inclusion_batch`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchInput {
+    pub head_block_root: Hash,
+    pub proofs: Vec<BasicProof>,
+}
+
+impl BatchInput {
+    /// Bytes the guest deserializes the input from, which can't exceed
+    /// `guest_batch::MAX_INPUT_SIZE`.
+    pub fn guest_input_size(&self) -> usize {
+        input_size(&self.head_block_root) + input_size(&self.proofs)
+    }
+}
+
+impl DryRun for BatchInput {
+    /// Each outcome id with whether its proof holds. A batch is never
+    /// rejected as a whole.
+    type Output = Vec<(Hash, bool)>;
+
+    fn dry_run(self, _: &ProtocolConfig) -> Result<Self::Output, Error> {
+        Ok(Protocol::verify_batch(&self.head_block_root, &self.proofs))
+    }
+}
+
+/// Runs the same `Protocol` code as the guests natively, so bad inputs fail in
+/// milliseconds rather than after proving, and services that don't need a
+/// proof can check inputs directly.
+#[derive(Debug, Clone)]
+pub struct Verifier {
+    config: ProtocolConfig,
+}
+
+impl Verifier {
+    pub fn new(config: ProtocolConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &ProtocolConfig {
+        &self.config
+    }
+
+    pub fn dry_run<I: DryRun>(&self, input: I) -> Result<I::Output, Error> {
+        input.dry_run(&self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{read_block, read_proof};
+    use crate::test_util::{fixture, fixture_json, sync_input};
+    use light_client_core::{combine_hash, CompactLightClientBlock, Network};
+
+    #[test]
+    fn test_dry_run_rejects_already_verified() {
+        let input = SyncInput {
+            next_block: read_block(&fixture("test_0.json")).unwrap(),
+            ..sync_input()
+        };
+
+        assert_eq!(
+            Verifier::new(Network::Testnet.config())
+                .dry_run(input)
+                .err(),
+            Some(Error::BlockAlreadyVerified)
+        );
+    }
+
+    #[test]
+    fn test_sync_compact_block() {
+        let input = sync_input();
+        let expected = Protocol::sync(&input.head, &input.epoch_bps, input.next_block.clone())
+            .map(SyncOutput::from);
+        let sync_compact = |compact: CompactLightClientBlock| {
+            Protocol::sync(&input.head, &input.epoch_bps, compact).map(SyncOutput::from)
+        };
+        let mut compact = CompactLightClientBlock::from(input.next_block.clone());
+
+        assert!(expected.is_ok());
+        assert_eq!(sync_compact(compact.clone()), expected);
+
+        // A bit set for a signature that isn't there
+        compact.signatures.truncate(compact.signatures.len() - 64);
+        assert_eq!(sync_compact(compact), Err(Error::ApprovalsMalformed));
+    }
+
+    #[test]
+    fn test_participation_names_signing_set() {
+        let input = sync_input();
+        let state = TrustedState::new(input.head.clone(), input.epoch_bps.clone());
+        let output = Verifier::new(Network::Testnet.config())
+            .dry_run(input.clone())
+            .unwrap();
+
+        // The producers that signed are the ones the head committed to
+        let next_bp_hash = input.head.inner_lite.next_bp_hash;
+        assert_eq!(output.participation.bps_hash, next_bp_hash);
+        assert_eq!(
+            state.commitment(),
+            combine_hash(&input.head.hash(), &next_bp_hash)
+        );
+        assert!((0..input.epoch_bps.len()).any(|i| output.participation.approved(i)));
+    }
+
+    #[test]
+    fn test_verify_request_fits_batch_guest() {
+        let input = fixture_json("verify_proof.json")["proof_request"]["data"]["input"].clone();
+        let bytes = hex::decode(input.as_str().unwrap().trim_start_matches("0x")).unwrap();
+        let Ok(types::Request::Verify(request)) = types::Request::decode(&bytes) else {
+            panic!("not a verify request");
+        };
+        // The fixture's proofs are typical, so stand in for each outcome's
+        let proof = read_proof(&fixture("old.json")).unwrap();
+        let proofs = request
+            .outcomes
+            .into_iter()
+            .map(|outcome| {
+                let mut proof = proof.clone();
+                proof.outcome_proof.id = outcome.id;
+                proof.outcome_proof.outcome.executor_id = outcome.account_id;
+                proof
+            })
+            .collect();
+        let input = BatchInput {
+            head_block_root: proof.block_header_lite.inner_lite.block_merkle_root,
+            proofs,
+        };
+
+        assert_eq!(input.proofs.len(), 128);
+        assert!(input.guest_input_size() <= guest_batch::MAX_INPUT_SIZE);
+    }
+
+    #[test]
+    fn test_batch_max_input_size_is_the_guests() {
+        // `jolt::provable` only takes literals, so the constant is checked
+        // against the attribute it mirrors
+        let guest = include_str!("../guests/batch/src/lib.rs");
+        let attribute = format!("max_input_size = {},", guest_batch::MAX_INPUT_SIZE);
+        assert!(guest.contains(&attribute));
+    }
+}
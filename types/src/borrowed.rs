@@ -0,0 +1,236 @@
+//! Borrowed counterparts of the views, encoding exactly as the owned types do,
+//! so hashes can be computed without cloning the views first.
+
+use crate::{
+    combine_hash, hash_borsh, AccountId, Balance, BlockHeaderInnerLiteView, BlockHeight,
+    CompactLightClientBlock, Hash, LightClientBlockLiteView, LightClientBlockView, PublicKey,
+    ValidatorStake, ValidatorStakeView, ValidatorStakeViewV1,
+};
+use alloc::vec::Vec;
+use borsh::{
+    io::{Error, Write},
+    BorshSerialize,
+};
+
+/// Borrowed `BlockHeaderInnerLite`, built from a `BlockHeaderInnerLiteView`.
+#[derive(BorshSerialize, Debug, Clone, Copy)]
+pub struct BlockHeaderInnerLiteRef<'a> {
+    pub height: &'a BlockHeight,
+    pub epoch_id: &'a Hash,
+    pub next_epoch_id: &'a Hash,
+    pub prev_state_root: &'a Hash,
+    pub prev_outcome_root: &'a Hash,
+    pub timestamp: &'a u64,
+    pub next_bp_hash: &'a Hash,
+    pub block_merkle_root: &'a Hash,
+}
+
+impl<'a> From<&'a BlockHeaderInnerLiteView> for BlockHeaderInnerLiteRef<'a> {
+    fn from(view: &'a BlockHeaderInnerLiteView) -> Self {
+        Self {
+            height: &view.height,
+            epoch_id: &view.epoch_id,
+            next_epoch_id: &view.next_epoch_id,
+            prev_state_root: &view.prev_state_root,
+            prev_outcome_root: &view.outcome_root,
+            timestamp: &view.timestamp_nanosec,
+            next_bp_hash: &view.next_bp_hash,
+            block_merkle_root: &view.block_merkle_root,
+        }
+    }
+}
+
+impl BlockHeaderInnerLiteView {
+    pub fn hash(&self) -> Hash {
+        hash_borsh(BlockHeaderInnerLiteRef::from(self))
+    }
+}
+
+/// Borrowed `Header`, built from a header or a light client block in either form.
+#[derive(Debug, Clone, Copy)]
+pub struct HeaderRef<'a> {
+    pub prev_block_hash: &'a Hash,
+    pub inner_rest_hash: &'a Hash,
+    pub inner_lite: &'a BlockHeaderInnerLiteView,
+}
+
+impl HeaderRef<'_> {
+    pub fn hash(&self) -> Hash {
+        combine_hash(
+            &combine_hash(&self.inner_lite.hash(), self.inner_rest_hash),
+            self.prev_block_hash,
+        )
+    }
+}
+
+impl<'a> From<&'a LightClientBlockLiteView> for HeaderRef<'a> {
+    fn from(header: &'a LightClientBlockLiteView) -> Self {
+        Self {
+            prev_block_hash: &header.prev_block_hash,
+            inner_rest_hash: &header.inner_rest_hash,
+            inner_lite: &header.inner_lite,
+        }
+    }
+}
+
+impl<'a> From<&'a LightClientBlockView> for HeaderRef<'a> {
+    fn from(block: &'a LightClientBlockView) -> Self {
+        Self {
+            prev_block_hash: &block.prev_block_hash,
+            inner_rest_hash: &block.inner_rest_hash,
+            inner_lite: &block.inner_lite,
+        }
+    }
+}
+
+impl LightClientBlockView {
+    pub fn header(&self) -> HeaderRef<'_> {
+        self.into()
+    }
+}
+
+impl<'a> From<&'a CompactLightClientBlock> for HeaderRef<'a> {
+    fn from(block: &'a CompactLightClientBlock) -> Self {
+        Self {
+            prev_block_hash: &block.prev_block_hash,
+            inner_rest_hash: &block.inner_rest_hash,
+            inner_lite: &block.inner_lite,
+        }
+    }
+}
+
+impl CompactLightClientBlock {
+    pub fn header(&self) -> HeaderRef<'_> {
+        self.into()
+    }
+}
+
+/// Borrowed `ValidatorStakeViewV1`, encoding as nearcore's `ValidatorStakeView`
+/// does within the `V1` variant.
+#[derive(Debug, Clone, Copy)]
+pub struct ValidatorStakeViewV1Ref<'a> {
+    pub account_id: &'a AccountId,
+    pub public_key: &'a PublicKey,
+    pub stake: &'a Balance,
+}
+
+impl BorshSerialize for ValidatorStakeViewV1Ref<'_> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        BorshSerialize::serialize(self.account_id, writer)?;
+        // PublicKey::ED25519
+        BorshSerialize::serialize(&0u8, writer)?;
+        writer.write_all(self.public_key)?;
+        BorshSerialize::serialize(self.stake, writer)
+    }
+}
+
+impl<'a> From<&'a ValidatorStakeViewV1> for ValidatorStakeViewV1Ref<'a> {
+    fn from(v1: &'a ValidatorStakeViewV1) -> Self {
+        Self {
+            account_id: &v1.account_id,
+            public_key: &v1.public_key,
+            stake: &v1.stake,
+        }
+    }
+}
+
+/// Borrowed `ValidatorStakeView`.
+#[derive(BorshSerialize, Debug, Clone, Copy)]
+pub enum ValidatorStakeViewRef<'a> {
+    V1(ValidatorStakeViewV1Ref<'a>),
+}
+
+impl<'a> From<&'a ValidatorStakeView> for ValidatorStakeViewRef<'a> {
+    fn from(view: &'a ValidatorStakeView) -> Self {
+        match view {
+            ValidatorStakeView::V1(v1) => Self::V1(v1.into()),
+        }
+    }
+}
+
+impl<'a> From<&'a ValidatorStake> for ValidatorStakeViewRef<'a> {
+    fn from(stake: &'a ValidatorStake) -> Self {
+        Self::V1(ValidatorStakeViewV1Ref {
+            account_id: &stake.account_id,
+            public_key: &stake.public_key,
+            stake: &stake.stake,
+        })
+    }
+}
+
+/// The `next_bp_hash` committing to a block producer set.
+pub fn bps_hash<'a, T: 'a>(bps: &'a [T]) -> Hash
+where
+    ValidatorStakeViewRef<'a>: From<&'a T>,
+{
+    hash_borsh(
+        bps.iter()
+            .map(ValidatorStakeViewRef::from)
+            .collect::<Vec<_>>(),
+    )
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::Header;
+
+    fn fixture(name: &str) -> near_primitives::views::LightClientBlockView {
+        let file: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(
+                std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                    .join("../fixtures")
+                    .join(name),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        serde_json::from_value(file["body"].clone()).unwrap()
+    }
+
+    #[test]
+    fn test_hashes_match_fixtures() {
+        for name in [
+            "main_0.json",
+            "main_1.json",
+            "main_2.json",
+            "test_0.json",
+            "test_1.json",
+            "test_2.json",
+        ] {
+            let view = fixture(name);
+            let expected = near_primitives::views::LightClientBlockLiteView {
+                prev_block_hash: view.prev_block_hash,
+                inner_rest_hash: view.inner_rest_hash,
+                inner_lite: view.inner_lite.clone(),
+            }
+            .hash()
+            .0;
+            let block = LightClientBlockView::from(view);
+            let header = Header {
+                prev_block_hash: block.prev_block_hash,
+                inner_rest_hash: block.inner_rest_hash,
+                inner_lite: block.inner_lite.clone(),
+            };
+            assert_eq!(block.header().hash(), expected, "{}", name);
+            assert_eq!(header.hash(), expected, "{}", name);
+
+            // The committed set, hashed borrowed and owned
+            let next_bps = block.next_bps.unwrap();
+            let stakes: Vec<ValidatorStake> = next_bps.iter().cloned().map(Into::into).collect();
+            assert_eq!(
+                bps_hash(&next_bps),
+                block.inner_lite.next_bp_hash,
+                "{}",
+                name
+            );
+            assert_eq!(bps_hash(&stakes), block.inner_lite.next_bp_hash, "{}", name);
+            assert_eq!(
+                hash_borsh(&next_bps),
+                block.inner_lite.next_bp_hash,
+                "{}",
+                name
+            );
+        }
+    }
+}
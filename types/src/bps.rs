@@ -0,0 +1,137 @@
+use crate::{AccountId, Balance, ValidatorStake};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+pub const BASIS_POINTS: u32 = 10_000;
+
+/// The stake of a block producer present in both sets.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StakeDelta {
+    pub account_id: AccountId,
+    pub before: Balance,
+    pub after: Balance,
+}
+
+/// How the block producer set changed from one epoch to the next.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BpsDiff {
+    pub joined: Vec<ValidatorStake>,
+    pub left: Vec<ValidatorStake>,
+    /// Producers in both sets whose stake changed.
+    pub stake_deltas: Vec<StakeDelta>,
+    /// Share of the previous total stake that rotated out, in basis points.
+    pub rotated_stake_bps: u32,
+}
+
+impl BpsDiff {
+    /// Stake rotates out when a producer leaves or its stake decreases.
+    pub fn compute(prev: &[ValidatorStake], next: &[ValidatorStake]) -> Self {
+        let prev_stakes: BTreeMap<&AccountId, &ValidatorStake> =
+            prev.iter().map(|vs| (&vs.account_id, vs)).collect();
+        let next_stakes: BTreeMap<&AccountId, &ValidatorStake> =
+            next.iter().map(|vs| (&vs.account_id, vs)).collect();
+
+        let joined = next
+            .iter()
+            .filter(|vs| !prev_stakes.contains_key(&vs.account_id))
+            .cloned()
+            .collect();
+        let left: Vec<ValidatorStake> = prev
+            .iter()
+            .filter(|vs| !next_stakes.contains_key(&vs.account_id))
+            .cloned()
+            .collect();
+        let stake_deltas: Vec<StakeDelta> = prev
+            .iter()
+            .filter_map(|vs| {
+                next_stakes
+                    .get(&vs.account_id)
+                    .filter(|next| next.stake != vs.stake)
+                    .map(|next| StakeDelta {
+                        account_id: vs.account_id.clone(),
+                        before: vs.stake,
+                        after: next.stake,
+                    })
+            })
+            .collect();
+
+        let total: Balance = prev.iter().map(|vs| vs.stake).sum();
+        let rotated: Balance = left.iter().map(|vs| vs.stake).sum::<Balance>()
+            + stake_deltas
+                .iter()
+                .map(|d| d.before.saturating_sub(d.after))
+                .sum::<Balance>();
+
+        let rotated_stake_bps = match total {
+            0 => 0,
+            total => match rotated.checked_mul(BASIS_POINTS as Balance) {
+                Some(scaled) => scaled / total,
+                // Dividing `total` first floors the divisor, so this can only
+                // overstate the rotation, which is the safe side of a bound
+                None => rotated / (total / BASIS_POINTS as Balance),
+            }
+            .min(BASIS_POINTS as Balance) as u32,
+        };
+
+        Self {
+            joined,
+            left,
+            stake_deltas,
+            rotated_stake_bps,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.joined.is_empty() && self.left.is_empty() && self.stake_deltas.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_bps_diff() {
+        let prev = vec![
+            ValidatorStake::new("a".into(), [0; 32], 50),
+            ValidatorStake::new("b".into(), [1; 32], 30),
+            ValidatorStake::new("c".into(), [2; 32], 20),
+        ];
+        let next = vec![
+            ValidatorStake::new("a".into(), [0; 32], 40),
+            ValidatorStake::new("c".into(), [2; 32], 20),
+            ValidatorStake::new("d".into(), [3; 32], 40),
+        ];
+
+        let diff = BpsDiff::compute(&prev, &next);
+
+        assert_eq!(diff.joined, vec![next[2].clone()]);
+        assert_eq!(diff.left, vec![prev[1].clone()]);
+        assert_eq!(
+            diff.stake_deltas,
+            vec![StakeDelta {
+                account_id: "a".into(),
+                before: 50,
+                after: 40
+            }]
+        );
+        // b's 30 left and a dropped 10, out of 100
+        assert_eq!(diff.rotated_stake_bps, 4_000);
+        assert!(BpsDiff::compute(&prev, &prev).is_empty());
+    }
+
+    #[test]
+    fn test_rotation_of_large_stakes() {
+        let prev = vec![
+            ValidatorStake::new("a".into(), [0; 32], Balance::MAX / 2),
+            ValidatorStake::new("b".into(), [1; 32], Balance::MAX / 4),
+        ];
+        let next = vec![prev[1].clone()];
+
+        // Two thirds left, though scaling the rotation by `BASIS_POINTS` overflows
+        assert_eq!(BpsDiff::compute(&prev, &next).rotated_stake_bps, 6_666);
+    }
+}
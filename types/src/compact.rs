@@ -0,0 +1,234 @@
+use crate::{BlockHeaderInnerLiteView, Hash, LightClientBlockView, Signature, ValidatorStakeView};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+use core::slice::ChunksExact;
+use serde::{Deserialize, Serialize};
+
+const SIGNATURE_LENGTH: usize = ed25519_dalek::SIGNATURE_LENGTH;
+
+/// A compact, lossless encoding of `LightClientBlockView` for guest inputs.
+///
+/// Approvals are a presence bitmap plus the present signatures packed end to
+/// end, rather than an `Option<Box<Signature>>` per seat.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CompactLightClientBlock {
+    pub prev_block_hash: Hash,
+    pub next_block_inner_hash: Hash,
+    pub inner_lite: BlockHeaderInnerLiteView,
+    pub inner_rest_hash: Hash,
+    /// Kept whole, as the account ids are part of `next_bp_hash`.
+    pub next_bps: Option<Vec<ValidatorStakeView>>,
+    /// Number of approval seats.
+    pub num_approvals: u32,
+    /// Bit `i` is set if seat `i` approved, least significant bit first.
+    pub approvals_bitmap: Vec<u8>,
+    /// Signatures of the approving seats, in seat order.
+    pub signatures: Vec<u8>,
+}
+
+impl CompactLightClientBlock {
+    pub fn approved(&self, seat: usize) -> bool {
+        self.approvals_bitmap
+            .get(seat / 8)
+            .is_some_and(|byte| byte & (1 << (seat % 8)) != 0)
+    }
+
+    /// The approval of each seat in order, read straight from the packed
+    /// signatures, if the bitmap has a bit per seat and one set per signature.
+    pub fn approvals(&self) -> Result<CompactApprovals<'_>, MalformedApprovals> {
+        let num_approvals = self.num_approvals as usize;
+        let num_set = self
+            .approvals_bitmap
+            .iter()
+            .map(|byte| byte.count_ones() as usize)
+            .sum::<usize>();
+        let num_approved = (0..num_approvals)
+            .filter(|&seat| self.approved(seat))
+            .count();
+
+        if self.approvals_bitmap.len() != num_approvals.div_ceil(8)
+            || num_set != num_approved
+            || self.signatures.len() != num_approved * SIGNATURE_LENGTH
+        {
+            return Err(MalformedApprovals);
+        }
+        Ok(CompactApprovals {
+            block: self,
+            seat: 0,
+            signatures: self.signatures.chunks_exact(SIGNATURE_LENGTH),
+        })
+    }
+}
+
+/// The approvals bitmap of a `CompactLightClientBlock` doesn't account for
+/// exactly its seats and signatures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MalformedApprovals;
+
+/// Iterator over the approvals of a well-formed `CompactLightClientBlock`.
+#[derive(Debug, Clone)]
+pub struct CompactApprovals<'a> {
+    block: &'a CompactLightClientBlock,
+    seat: usize,
+    signatures: ChunksExact<'a, u8>,
+}
+
+impl Iterator for CompactApprovals<'_> {
+    type Item = Option<ed25519_dalek::Signature>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.seat == self.block.num_approvals as usize {
+            return None;
+        }
+        let approved = self.block.approved(self.seat);
+        self.seat += 1;
+        if !approved {
+            return Some(None);
+        }
+        // `approvals` checked there's a signature for each set bit
+        let bytes: &[u8; SIGNATURE_LENGTH] = self.signatures.next()?.try_into().ok()?;
+        Some(Some(ed25519_dalek::Signature::from_bytes(bytes)))
+    }
+}
+
+impl From<LightClientBlockView> for CompactLightClientBlock {
+    fn from(view: LightClientBlockView) -> Self {
+        let num_approvals = view.approvals_after_next.len();
+        let mut approvals_bitmap = alloc::vec![0; num_approvals.div_ceil(8)];
+        let mut signatures = Vec::new();
+
+        for (seat, approval) in view.approvals_after_next.iter().enumerate() {
+            if let Some(signature) = approval {
+                approvals_bitmap[seat / 8] |= 1 << (seat % 8);
+                signatures.extend_from_slice(&signature.0.to_bytes());
+            }
+        }
+
+        Self {
+            prev_block_hash: view.prev_block_hash,
+            next_block_inner_hash: view.next_block_inner_hash,
+            inner_lite: view.inner_lite,
+            inner_rest_hash: view.inner_rest_hash,
+            next_bps: view.next_bps,
+            num_approvals: num_approvals as u32,
+            approvals_bitmap,
+            signatures,
+        }
+    }
+}
+
+impl TryFrom<CompactLightClientBlock> for LightClientBlockView {
+    type Error = MalformedApprovals;
+
+    fn try_from(compact: CompactLightClientBlock) -> Result<Self, Self::Error> {
+        let approvals_after_next = compact
+            .approvals()?
+            .map(|approval| approval.map(|signature| Box::new(Signature(signature))))
+            .collect();
+
+        Ok(Self {
+            prev_block_hash: compact.prev_block_hash,
+            next_block_inner_hash: compact.next_block_inner_hash,
+            inner_lite: compact.inner_lite,
+            inner_rest_hash: compact.inner_rest_hash,
+            next_bps: compact.next_bps,
+            approvals_after_next,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_compact_roundtrip() {
+        let signature = |byte: u8| {
+            Some(Box::new(Signature(ed25519_dalek::Signature::from_bytes(
+                &[byte; SIGNATURE_LENGTH],
+            ))))
+        };
+        let view = LightClientBlockView {
+            prev_block_hash: [1; 32],
+            next_block_inner_hash: [2; 32],
+            inner_lite: BlockHeaderInnerLiteView {
+                height: 10,
+                epoch_id: [3; 32],
+                next_epoch_id: [4; 32],
+                prev_state_root: [5; 32],
+                outcome_root: [6; 32],
+                timestamp: 7,
+                timestamp_nanosec: 7,
+                next_bp_hash: [8; 32],
+                block_merkle_root: [9; 32],
+            },
+            inner_rest_hash: [10; 32],
+            next_bps: Some(vec![ValidatorStakeView::new("a".into(), [11; 32], 12)]),
+            approvals_after_next: (0..10)
+                .map(|seat| if seat % 3 == 0 { None } else { signature(seat) })
+                .collect(),
+        };
+
+        let compact = CompactLightClientBlock::from(view.clone());
+        assert_eq!(compact.num_approvals, 10);
+        assert_eq!(compact.signatures.len(), 6 * SIGNATURE_LENGTH);
+        assert_eq!(LightClientBlockView::try_from(compact).unwrap(), view);
+    }
+
+    #[test]
+    fn test_malformed_approvals() {
+        let compact = CompactLightClientBlock {
+            prev_block_hash: [1; 32],
+            next_block_inner_hash: [2; 32],
+            inner_lite: BlockHeaderInnerLiteView {
+                height: 10,
+                epoch_id: [3; 32],
+                next_epoch_id: [4; 32],
+                prev_state_root: [5; 32],
+                outcome_root: [6; 32],
+                timestamp: 7,
+                timestamp_nanosec: 7,
+                next_bp_hash: [8; 32],
+                block_merkle_root: [9; 32],
+            },
+            inner_rest_hash: [10; 32],
+            next_bps: None,
+            num_approvals: 10,
+            approvals_bitmap: vec![0b0000_0101, 0b0000_0010],
+            signatures: vec![11; 3 * SIGNATURE_LENGTH],
+        };
+        assert_eq!(compact.approvals().unwrap().count(), 10);
+
+        let malformed = [
+            // A signature without a bit
+            CompactLightClientBlock {
+                signatures: vec![11; 4 * SIGNATURE_LENGTH],
+                ..compact.clone()
+            },
+            // A bit without a signature
+            CompactLightClientBlock {
+                signatures: vec![11; 2 * SIGNATURE_LENGTH],
+                ..compact.clone()
+            },
+            // A bit past the last seat
+            CompactLightClientBlock {
+                approvals_bitmap: vec![0b0000_0101, 0b0100_0000],
+                ..compact.clone()
+            },
+            // Too few bytes for every seat
+            CompactLightClientBlock {
+                approvals_bitmap: vec![0b0000_0111],
+                ..compact.clone()
+            },
+        ];
+        for compact in malformed {
+            assert_eq!(compact.approvals().err(), Some(MalformedApprovals));
+            assert_eq!(
+                LightClientBlockView::try_from(compact).err(),
+                Some(MalformedApprovals)
+            );
+        }
+    }
+}
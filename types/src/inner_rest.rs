@@ -0,0 +1,288 @@
+use crate::{hash, AccountId, Balance, BlockHeight, Hash, MerkleHash, Signature, ValidatorStake};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use borsh::{
+    io::{Error, ErrorKind, Read, Write},
+    BorshDeserialize, BorshSerialize,
+};
+use serde::{Deserialize, Serialize};
+
+pub type ProtocolVersion = u32;
+
+/// A validator stake as borsh-encoded in block headers, where both the stake and
+/// the public key are versioned enums.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ValidatorProposal(pub ValidatorStake);
+
+impl BorshSerialize for ValidatorProposal {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        // ValidatorStake::V1
+        BorshSerialize::serialize(&0u8, writer)?;
+        BorshSerialize::serialize(&self.0.account_id, writer)?;
+        // PublicKey::ED25519
+        BorshSerialize::serialize(&0u8, writer)?;
+        writer.write_all(&self.0.public_key)?;
+        BorshSerialize::serialize(&self.0.stake, writer)
+    }
+}
+
+impl BorshDeserialize for ValidatorProposal {
+    fn deserialize_reader<R: Read>(rd: &mut R) -> Result<Self, Error> {
+        if u8::deserialize_reader(rd)? != 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "unknown stake version"));
+        }
+        let account_id = AccountId::deserialize_reader(rd)?;
+        if u8::deserialize_reader(rd)? != 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "unsupported key type"));
+        }
+        let public_key = BorshDeserialize::deserialize_reader(rd)?;
+        let stake = Balance::deserialize_reader(rd)?;
+        Ok(Self(ValidatorStake::new(account_id, public_key, stake)))
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SlashedValidator {
+    pub account_id: AccountId,
+    pub is_double_sign: bool,
+}
+
+pub type ChallengesResult = Vec<SlashedValidator>;
+
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq,
+)]
+pub struct BlockHeaderInnerRestV3 {
+    /// Root hash of the chunk receipts in the given block.
+    pub chunk_receipts_root: MerkleHash,
+    /// Root hash of the chunk headers in the given block.
+    pub chunk_headers_root: MerkleHash,
+    /// Root hash of the chunk transactions in the given block.
+    pub chunk_tx_root: MerkleHash,
+    /// Root hash of the challenges in the given block.
+    pub challenges_root: MerkleHash,
+    /// The output of the randomness beacon
+    pub random_value: Hash,
+    /// Validator proposals from the previous chunks.
+    pub prev_validator_proposals: Vec<ValidatorProposal>,
+    /// Mask for new chunks included in the block
+    pub chunk_mask: Vec<bool>,
+    /// Gas price for chunks in the next block.
+    pub next_gas_price: Balance,
+    /// Total supply of tokens in the system
+    pub total_supply: Balance,
+    /// List of challenges result from previous block.
+    pub challenges_result: ChallengesResult,
+    /// Last block that has full BFT finality
+    pub last_final_block: Hash,
+    /// Last block that has doomslug finality
+    pub last_ds_final_block: Hash,
+    /// The ordinal of the Block on the Canonical Chain
+    pub block_ordinal: u64,
+    pub prev_height: BlockHeight,
+    pub epoch_sync_data_hash: Option<Hash>,
+    /// All the approvals included in this block
+    pub approvals: Vec<Option<Box<Signature>>>,
+    /// Latest protocol version that this block producer has.
+    pub latest_protocol_version: ProtocolVersion,
+}
+
+/// As `BlockHeaderInnerRestV3`, with the hash of the block body added.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq,
+)]
+pub struct BlockHeaderInnerRestV4 {
+    /// Hash of block body
+    pub block_body_hash: Hash,
+    pub chunk_receipts_root: MerkleHash,
+    pub chunk_headers_root: MerkleHash,
+    pub chunk_tx_root: MerkleHash,
+    pub challenges_root: MerkleHash,
+    pub random_value: Hash,
+    pub prev_validator_proposals: Vec<ValidatorProposal>,
+    pub chunk_mask: Vec<bool>,
+    pub next_gas_price: Balance,
+    pub total_supply: Balance,
+    pub challenges_result: ChallengesResult,
+    pub last_final_block: Hash,
+    pub last_ds_final_block: Hash,
+    pub block_ordinal: u64,
+    pub prev_height: BlockHeight,
+    pub epoch_sync_data_hash: Option<Hash>,
+    pub approvals: Vec<Option<Box<Signature>>>,
+    pub latest_protocol_version: ProtocolVersion,
+}
+
+/// The preimage of a header's `inner_rest_hash`, for the header versions
+/// currently produced. `V5` headers, which add `chunk_endorsements` for
+/// stateless validation, are not supported: their preimage can't be expressed
+/// here, so their random value and other fields can't be verified.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum BlockHeaderInnerRest {
+    V3(BlockHeaderInnerRestV3),
+    V4(BlockHeaderInnerRestV4),
+}
+
+impl BlockHeaderInnerRest {
+    /// The hash committed to as `inner_rest_hash`, which covers the versioned
+    /// struct but not the enum tag.
+    pub fn hash(&self) -> Hash {
+        let bytes = match self {
+            Self::V3(inner) => borsh::to_vec(inner),
+            Self::V4(inner) => borsh::to_vec(inner),
+        };
+        hash(&bytes.unwrap())
+    }
+
+    pub fn chunk_headers_root(&self) -> &MerkleHash {
+        match self {
+            Self::V3(inner) => &inner.chunk_headers_root,
+            Self::V4(inner) => &inner.chunk_headers_root,
+        }
+    }
+
+    pub fn random_value(&self) -> &Hash {
+        match self {
+            Self::V3(inner) => &inner.random_value,
+            Self::V4(inner) => &inner.random_value,
+        }
+    }
+
+    pub fn next_gas_price(&self) -> Balance {
+        match self {
+            Self::V3(inner) => inner.next_gas_price,
+            Self::V4(inner) => inner.next_gas_price,
+        }
+    }
+
+    pub fn total_supply(&self) -> Balance {
+        match self {
+            Self::V3(inner) => inner.total_supply,
+            Self::V4(inner) => inner.total_supply,
+        }
+    }
+
+    pub fn prev_validator_proposals(&self) -> &[ValidatorProposal] {
+        match self {
+            Self::V3(inner) => &inner.prev_validator_proposals,
+            Self::V4(inner) => &inner.prev_validator_proposals,
+        }
+    }
+}
+
+/// Fields of `BlockHeaderInnerRest` exposed for a verified header.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedInnerRest {
+    pub block_hash: Hash,
+    pub chunk_headers_root: MerkleHash,
+    pub next_gas_price: Balance,
+    pub total_supply: Balance,
+    pub random_value: Hash,
+    pub validator_proposals: Vec<ValidatorStake>,
+}
+
+impl VerifiedInnerRest {
+    pub fn new(block_hash: Hash, inner_rest: &BlockHeaderInnerRest) -> Self {
+        Self {
+            block_hash,
+            chunk_headers_root: *inner_rest.chunk_headers_root(),
+            next_gas_price: inner_rest.next_gas_price(),
+            total_supply: inner_rest.total_supply(),
+            random_value: *inner_rest.random_value(),
+            validator_proposals: inner_rest
+                .prev_validator_proposals()
+                .iter()
+                .map(|p| p.0.clone())
+                .collect(),
+        }
+    }
+}
+
+/// A block's `random_value`, as a verifiable source of randomness.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RandomBeacon {
+    pub height: BlockHeight,
+    pub block_hash: Hash,
+    pub random_value: Hash,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn samples() -> [BlockHeaderInnerRest; 2] {
+        let v3 = BlockHeaderInnerRestV3 {
+            chunk_receipts_root: [1; 32],
+            chunk_headers_root: [2; 32],
+            random_value: [3; 32],
+            prev_validator_proposals: vec![ValidatorProposal(ValidatorStake::new(
+                "test.near".into(),
+                [4; 32],
+                5,
+            ))],
+            chunk_mask: vec![true, false],
+            next_gas_price: 6,
+            total_supply: 7,
+            challenges_result: vec![SlashedValidator {
+                account_id: "slashed.near".into(),
+                is_double_sign: true,
+            }],
+            last_final_block: [8; 32],
+            block_ordinal: 9,
+            prev_height: 10,
+            epoch_sync_data_hash: Some([11; 32]),
+            approvals: vec![None],
+            latest_protocol_version: 63,
+            ..Default::default()
+        };
+        let v4 = BlockHeaderInnerRestV4 {
+            block_body_hash: [12; 32],
+            chunk_headers_root: [2; 32],
+            random_value: [3; 32],
+            chunk_mask: vec![true],
+            next_gas_price: 6,
+            total_supply: 7,
+            last_ds_final_block: [8; 32],
+            prev_height: 10,
+            approvals: vec![None, None],
+            latest_protocol_version: 64,
+            ..Default::default()
+        };
+        [BlockHeaderInnerRest::V3(v3), BlockHeaderInnerRest::V4(v4)]
+    }
+
+    #[test]
+    fn test_hash_excludes_version() {
+        let [v3, v4] = samples();
+        let BlockHeaderInnerRest::V3(inner) = &v3 else {
+            unreachable!()
+        };
+        assert_eq!(v3.hash(), hash(&borsh::to_vec(inner).unwrap()));
+        assert_ne!(v3.hash(), hash(&borsh::to_vec(&v3).unwrap()));
+        assert_ne!(v3.hash(), v4.hash());
+    }
+
+    /// nearcore reads each preimage back whole, and hashes it the same.
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_hash_matches_nearcore() {
+        use near_primitives::block_header::{
+            BlockHeaderInnerRestV3 as V3, BlockHeaderInnerRestV4 as V4,
+        };
+
+        for inner_rest in samples() {
+            let expected = match &inner_rest {
+                BlockHeaderInnerRest::V3(inner) => {
+                    let near = V3::try_from_slice(&borsh::to_vec(inner).unwrap()).unwrap();
+                    near_primitives::hash::CryptoHash::hash_borsh(near)
+                }
+                BlockHeaderInnerRest::V4(inner) => {
+                    let near = V4::try_from_slice(&borsh::to_vec(inner).unwrap()).unwrap();
+                    near_primitives::hash::CryptoHash::hash_borsh(near)
+                }
+            };
+            assert_eq!(inner_rest.hash(), expected.0);
+        }
+    }
+}
@@ -1,11 +1,17 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use alloc::{boxed::Box, string::String};
+pub use borrowed::*;
 use borsh::{
     io::{Error, ErrorKind, Read, Write},
     BorshDeserialize, BorshSerialize,
 };
+pub use bps::*;
+pub use compact::*;
+pub use inner_rest::*;
 pub use merkle::*;
+pub use network::*;
+pub use request::*;
 use serde::{Deserialize, Serialize};
 use serde_with::base64::Base64;
 use serde_with::serde_as;
@@ -14,9 +20,16 @@ pub extern crate alloc;
 pub use alloc::*;
 pub use vec::Vec;
 
+mod borrowed;
+mod bps;
+mod compact;
+mod inner_rest;
 mod merkle;
+mod network;
+mod request;
 
 pub type BlockHeight = u64;
+pub type ShardId = u64;
 pub type EpochId = Hash;
 pub type Balance = u128;
 pub type AccountId = String;
@@ -111,7 +124,7 @@ impl From<near_primitives::views::BlockHeaderInnerLiteView> for BlockHeaderInner
     }
 }
 
-#[derive(Debug, Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
 pub struct RpcLightClientExecutionProofResponse {
     pub outcome_proof: ExecutionOutcomeWithIdView,
     pub outcome_root_proof: MerklePath,
@@ -119,6 +132,37 @@ pub struct RpcLightClientExecutionProofResponse {
     pub block_proof: MerklePath,
 }
 
+#[cfg(feature = "std")]
+mod rpc {
+    use super::*;
+
+    /// The proof as nearcore's RPC encodes it.
+    #[derive(Deserialize)]
+    struct NearProof {
+        outcome_proof: near_primitives::views::ExecutionOutcomeWithIdView,
+        outcome_root_proof: near_primitives::merkle::MerklePath,
+        block_header_lite: near_primitives::views::LightClientBlockLiteView,
+        block_proof: near_primitives::merkle::MerklePath,
+    }
+
+    impl RpcLightClientExecutionProofResponse {
+        /// Parse a proof in the JSON returned by `EXPERIMENTAL_light_client_proof`.
+        pub fn from_rpc_json(json: &str) -> Result<Self, serde_json::Error> {
+            let proof: NearProof = serde_json::from_str(json)?;
+            Ok(Self {
+                outcome_proof: proof.outcome_proof.into(),
+                outcome_root_proof: proof
+                    .outcome_root_proof
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+                block_header_lite: proof.block_header_lite.into(),
+                block_proof: proof.block_proof.into_iter().map(Into::into).collect(),
+            })
+        }
+    }
+}
+
 #[derive(
     BorshSerialize,
     BorshDeserialize,
@@ -136,6 +180,18 @@ pub struct ExecutionOutcomeWithIdView {
     pub outcome: ExecutionOutcomeView,
 }
 
+#[cfg(feature = "std")]
+impl From<near_primitives::views::ExecutionOutcomeWithIdView> for ExecutionOutcomeWithIdView {
+    fn from(value: near_primitives::views::ExecutionOutcomeWithIdView) -> Self {
+        Self {
+            proof: value.proof.into_iter().map(Into::into).collect(),
+            block_hash: value.block_hash.0,
+            id: value.id.0,
+            outcome: value.outcome.into(),
+        }
+    }
+}
+
 #[derive(
     BorshSerialize,
     BorshDeserialize,
@@ -164,6 +220,20 @@ pub struct ExecutionOutcomeView {
     /// Execution status
     pub status: PartialExecutionStatus,
 }
+#[cfg(feature = "std")]
+impl From<near_primitives::views::ExecutionOutcomeView> for ExecutionOutcomeView {
+    fn from(value: near_primitives::views::ExecutionOutcomeView) -> Self {
+        Self {
+            logs: value.logs,
+            receipt_ids: value.receipt_ids.into_iter().map(|id| id.0).collect(),
+            gas_burnt: value.gas_burnt,
+            tokens_burnt: value.tokens_burnt,
+            executor_id: value.executor_id.to_string(),
+            status: value.status.into(),
+        }
+    }
+}
+
 impl ExecutionOutcomeView {
     // Same behavior as ExecutionOutcomeWithId's to_hashes.
     pub fn to_hashes(&self, id: Hash) -> Vec<Hash> {
@@ -206,6 +276,19 @@ pub enum PartialExecutionStatus {
     SuccessReceiptId(Hash),
 }
 
+#[cfg(feature = "std")]
+impl From<near_primitives::views::ExecutionStatusView> for PartialExecutionStatus {
+    fn from(value: near_primitives::views::ExecutionStatusView) -> Self {
+        use near_primitives::views::ExecutionStatusView::*;
+        match value {
+            Unknown => Self::Unknown,
+            Failure(_) => Self::Failure,
+            SuccessValue(value) => Self::SuccessValue(value),
+            SuccessReceiptId(id) => Self::SuccessReceiptId(id.0),
+        }
+    }
+}
+
 #[serde_as]
 #[derive(
     BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone,
@@ -308,16 +391,7 @@ impl From<near_primitives::views::validator_stake_view::ValidatorStakeView> for
     }
 }
 
-#[derive(
-    Debug,
-    Clone,
-    Eq,
-    PartialEq,
-    BorshSerialize,
-    BorshDeserialize,
-    serde::Serialize,
-    serde::Deserialize,
-)]
+#[derive(Debug, Clone, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ValidatorStakeViewV1 {
     pub account_id: AccountId,
     pub public_key: PublicKey,
@@ -334,6 +408,51 @@ impl ValidatorStakeViewV1 {
     }
 }
 
+impl BorshSerialize for ValidatorStakeViewV1 {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        BorshSerialize::serialize(&ValidatorStakeViewV1Ref::from(self), writer)
+    }
+}
+
+impl BorshDeserialize for ValidatorStakeViewV1 {
+    fn deserialize_reader<R: Read>(rd: &mut R) -> Result<Self, Error> {
+        let account_id = AccountId::deserialize_reader(rd)?;
+        // PublicKey::ED25519
+        if u8::deserialize_reader(rd)? != 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "unknown key type"));
+        }
+        Ok(Self {
+            account_id,
+            public_key: BorshDeserialize::deserialize_reader(rd)?,
+            stake: BorshDeserialize::deserialize_reader(rd)?,
+        })
+    }
+}
+
+/// Why an `AccountId` of ours, which is any string, isn't one nearcore accepts.
+#[cfg(feature = "std")]
+pub type InvalidAccountId = <near_primitives::types::AccountId as std::str::FromStr>::Err;
+
+#[cfg(feature = "std")]
+impl TryFrom<ValidatorStakeView>
+    for near_primitives::views::validator_stake_view::ValidatorStakeView
+{
+    type Error = InvalidAccountId;
+
+    fn try_from(value: ValidatorStakeView) -> Result<Self, Self::Error> {
+        let ValidatorStakeView::V1(v1) = value;
+        Ok(Self::V1(
+            near_primitives::views::validator_stake_view::ValidatorStakeViewV1 {
+                account_id: v1.account_id.parse()?,
+                public_key: near_crypto::PublicKey::ED25519(near_crypto::ED25519PublicKey(
+                    v1.public_key,
+                )),
+                stake: v1.stake,
+            },
+        ))
+    }
+}
+
 impl From<ValidatorStakeView> for ValidatorStake {
     fn from(view: ValidatorStakeView) -> Self {
         match view {
@@ -383,6 +502,23 @@ impl From<near_primitives::views::BlockHeaderInnerLiteView> for BlockHeaderInner
     }
 }
 
+#[cfg(feature = "std")]
+impl From<BlockHeaderInnerLiteView> for near_primitives::views::BlockHeaderInnerLiteView {
+    fn from(value: BlockHeaderInnerLiteView) -> Self {
+        Self {
+            height: value.height,
+            epoch_id: near_primitives::hash::CryptoHash(value.epoch_id),
+            next_epoch_id: near_primitives::hash::CryptoHash(value.next_epoch_id),
+            prev_state_root: near_primitives::hash::CryptoHash(value.prev_state_root),
+            outcome_root: near_primitives::hash::CryptoHash(value.outcome_root),
+            timestamp: value.timestamp,
+            timestamp_nanosec: value.timestamp_nanosec,
+            next_bp_hash: near_primitives::hash::CryptoHash(value.next_bp_hash),
+            block_merkle_root: near_primitives::hash::CryptoHash(value.block_merkle_root),
+        }
+    }
+}
+
 #[derive(
     PartialEq,
     Eq,
@@ -431,7 +567,50 @@ impl From<near_primitives::views::LightClientBlockView> for LightClientBlockView
     }
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, BorshDeserialize, BorshSerialize)]
+#[cfg(feature = "std")]
+impl TryFrom<LightClientBlockView> for near_primitives::views::LightClientBlockView {
+    type Error = InvalidAccountId;
+
+    fn try_from(value: LightClientBlockView) -> Result<Self, Self::Error> {
+        Ok(Self {
+            prev_block_hash: near_primitives::hash::CryptoHash(value.prev_block_hash),
+            next_block_inner_hash: near_primitives::hash::CryptoHash(value.next_block_inner_hash),
+            inner_lite: value.inner_lite.into(),
+            inner_rest_hash: near_primitives::hash::CryptoHash(value.inner_rest_hash),
+            next_bps: value
+                .next_bps
+                .map(|v| v.into_iter().map(TryInto::try_into).collect())
+                .transpose()?,
+            approvals_after_next: value
+                .approvals_after_next
+                .into_iter()
+                .map(|s| {
+                    s.map(|s| {
+                        // The 64 bytes of an ed25519 signature always parse
+                        Box::new(
+                            near_crypto::Signature::from_parts(
+                                near_crypto::KeyType::ED25519,
+                                &s.0.to_bytes(),
+                            )
+                            .expect("ed25519 signature"),
+                        )
+                    })
+                })
+                .collect(),
+        })
+    }
+}
+
+#[derive(
+    serde::Serialize,
+    serde::Deserialize,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshDeserialize,
+    BorshSerialize,
+)]
 pub struct LightClientBlockLiteView {
     pub prev_block_hash: Hash,
     pub inner_rest_hash: Hash,
@@ -440,14 +619,7 @@ pub struct LightClientBlockLiteView {
 
 impl LightClientBlockLiteView {
     pub fn hash(&self) -> Hash {
-        let block_header_inner_lite: BlockHeaderInnerLite = self.inner_lite.clone().into();
-        combine_hash(
-            &combine_hash(
-                &hash(&borsh::to_vec(&block_header_inner_lite).unwrap()),
-                &self.inner_rest_hash,
-            ),
-            &self.prev_block_hash,
-        )
+        HeaderRef::from(self).hash()
     }
 }
 
@@ -501,7 +673,7 @@ pub struct ActionError {
     pub index: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum LcProof {
     Basic {
@@ -539,3 +711,69 @@ impl From<(u128, u128)> for StakeInfo {
         Self { total, approved }
     }
 }
+
+/// Which block producers of an epoch approved a block.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Participation {
+    /// `bps_hash` of the block producer set the bitmap is aligned with, as
+    /// committed to by the `next_bp_hash` of a block of the epoch before.
+    pub bps_hash: Hash,
+    /// Bit `i` is set if the `i`th block producer approved, least significant bit first.
+    /// Only the seats whose approvals are checked have a bit.
+    pub bitmap: Vec<u8>,
+    /// Indices of block producers without an approval. Producers past the
+    /// checked seats are never listed.
+    pub missing: Vec<u32>,
+    /// Indices of block producers whose approval failed to verify.
+    pub invalid: Vec<u32>,
+}
+
+impl Participation {
+    pub fn new(bps_hash: Hash, num_bps: usize) -> Self {
+        Self {
+            bps_hash,
+            bitmap: alloc::vec![0; num_bps.div_ceil(8)],
+            missing: Vec::new(),
+            invalid: Vec::new(),
+        }
+    }
+
+    pub fn approve(&mut self, index: usize) {
+        self.bitmap[index / 8] |= 1 << (index % 8);
+    }
+
+    pub fn approved(&self, index: usize) -> bool {
+        self.bitmap
+            .get(index / 8)
+            .is_some_and(|byte| byte & (1 << (index % 8)) != 0)
+    }
+}
+
+/// The state a light client trusts: its head and the block producers expected
+/// to sign the next block it syncs to.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TrustedState {
+    pub head: Header,
+    pub epoch_bps: Vec<ValidatorStake>,
+}
+
+impl TrustedState {
+    pub fn new(head: Header, epoch_bps: Vec<ValidatorStake>) -> Self {
+        Self { head, epoch_bps }
+    }
+
+    /// Commitment to the state, binding the head to the producers trusted for it.
+    pub fn commitment(&self) -> Hash {
+        combine_hash(&self.head.hash(), &bps_hash(&self.epoch_bps))
+    }
+}
+
+/// An execution outcome proven to be included in a block.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedOutcome {
+    /// The shard whose chunk produced the outcome.
+    pub shard_id: ShardId,
+    pub block_hash: Hash,
+    pub id: Hash,
+    pub outcome: ExecutionOutcomeView,
+}
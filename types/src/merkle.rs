@@ -14,6 +14,19 @@ pub struct MerklePathItem {
 
 pub type MerklePath = Vec<MerklePathItem>;
 
+#[cfg(feature = "std")]
+impl From<near_primitives::merkle::MerklePathItem> for MerklePathItem {
+    fn from(value: near_primitives::merkle::MerklePathItem) -> Self {
+        Self {
+            hash: value.hash.0,
+            direction: match value.direction {
+                near_primitives::merkle::Direction::Left => Direction::Left,
+                near_primitives::merkle::Direction::Right => Direction::Right,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 pub enum Direction {
     Left,
@@ -50,22 +63,82 @@ pub fn compute_root_from_path<'a>(
     hash_so_far
 }
 
+/// The directions of the merkle path to the leaf at `index` in a tree of `len`
+/// leaves, as built by nearcore's `merklize`. A node without a sibling is
+/// carried up a level as-is, so no path item is produced for it.
+pub fn path_directions(mut index: u64, mut len: u64) -> Vec<Direction> {
+    let mut directions = Vec::new();
+    while len > 1 {
+        if index % 2 == 1 {
+            directions.push(Direction::Left);
+        } else if index + 1 < len {
+            directions.push(Direction::Right);
+        }
+        index /= 2;
+        len = (len + 1) / 2;
+    }
+    directions
+}
+
+/// The root of a merkle tree over `leaves`, and the path to each of them, as
+/// built by nearcore's `merklize` for leaves that are already hashes.
+pub fn merklize(leaves: &[MerkleHash]) -> (MerkleHash, Vec<MerklePath>) {
+    if leaves.is_empty() {
+        return (MerkleHash::default(), Vec::new());
+    }
+
+    let mut paths = alloc::vec![MerklePath::new(); leaves.len()];
+    // Each node of the current level, with the leaves under it
+    let mut level: Vec<(MerkleHash, Vec<usize>)> = leaves
+        .iter()
+        .enumerate()
+        .map(|(i, leaf)| (*leaf, alloc::vec![i]))
+        .collect();
+
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity(level.len().div_ceil(2));
+        let mut nodes = level.into_iter();
+        while let Some((left, mut left_leaves)) = nodes.next() {
+            let Some((right, right_leaves)) = nodes.next() else {
+                next.push((left, left_leaves));
+                break;
+            };
+            for &i in &left_leaves {
+                paths[i].push(MerklePathItem {
+                    hash: right,
+                    direction: Direction::Right,
+                });
+            }
+            for &i in &right_leaves {
+                paths[i].push(MerklePathItem {
+                    hash: left,
+                    direction: Direction::Left,
+                });
+            }
+            left_leaves.extend(right_leaves);
+            next.push((combine_hash(&left, &right), left_leaves));
+        }
+        level = next;
+    }
+
+    (level[0].0, paths)
+}
+
 pub fn hash(data: &[u8]) -> Hash {
     let mut hasher = sha2::Sha256::default();
     hasher.update(data);
     hasher.finalize().into()
 }
 
-pub fn hash_borsh<T>(value: T) -> Hash {
-    todo!("provide intobytes from host")
-    // let mut hasher = sha2::Sha256::default();
-    // let mut bytes = Vec::new();
-    // value.serialize(&mut bytes).unwrap();
-    // hasher.update(bytes);
-    // hasher.finalize().into()
+pub fn hash_borsh<T: BorshSerialize>(value: T) -> Hash {
+    let mut hasher = sha2::Sha256::default();
+    let mut bytes = Vec::new();
+    value.serialize(&mut bytes).unwrap();
+    hasher.update(bytes);
+    hasher.finalize().into()
 }
 
-pub fn compute_root_from_path_and_item<'a, T>(
+pub fn compute_root_from_path_and_item<'a, T: BorshSerialize>(
     path: impl Iterator<Item = &'a MerklePathItem>,
     item: T,
 ) -> MerkleHash {
@@ -75,9 +148,32 @@ pub fn compute_root_from_path_and_item<'a, T>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec;
 
     #[test]
     fn test_name() {
         todo!()
     }
+
+    #[test]
+    fn test_path_directions() {
+        use Direction::*;
+        assert_eq!(path_directions(0, 1), vec![]);
+        assert_eq!(path_directions(2, 4), vec![Right, Left]);
+        assert_eq!(path_directions(0, 5), vec![Right, Right, Right]);
+        // The last of an odd number of leaves is carried up without a sibling
+        assert_eq!(path_directions(4, 5), vec![Left]);
+    }
+
+    #[test]
+    fn test_merklize() {
+        let leaves: Vec<MerkleHash> = (0..5u8).map(|i| [i; 32]).collect();
+        let (root, paths) = merklize(&leaves);
+
+        for (i, (leaf, path)) in leaves.iter().zip(&paths).enumerate() {
+            assert!(verify_hash(root, path.iter(), *leaf));
+            let directions: Vec<_> = path.iter().map(|item| item.direction.clone()).collect();
+            assert_eq!(directions, path_directions(i as u64, leaves.len() as u64));
+        }
+    }
 }
@@ -0,0 +1,153 @@
+use crate::{ShardId, TrustedState};
+use alloc::boxed::Box;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+/// Parameters of the chain the light client follows.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolConfig {
+    /// Number of shards, which fixes the shape of outcome root proofs.
+    pub num_shards: ShardId,
+    /// Upper bound on the share of stake, in basis points, that may rotate out
+    /// of the block producer set between epochs.
+    pub max_rotated_stake_bps: Option<u32>,
+}
+
+/// A network to sync from, with the checkpoint to start at.
+#[derive(Debug, Clone)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Localnet,
+    Custom {
+        checkpoint: Box<TrustedState>,
+        config: ProtocolConfig,
+    },
+}
+
+impl Network {
+    pub fn config(&self) -> ProtocolConfig {
+        match self {
+            // Shard counts as of the embedded checkpoints
+            Self::Mainnet | Self::Testnet => ProtocolConfig {
+                num_shards: 4,
+                max_rotated_stake_bps: None,
+            },
+            Self::Localnet => ProtocolConfig {
+                num_shards: 1,
+                max_rotated_stake_bps: None,
+            },
+            Self::Custom { config, .. } => config.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod checkpoint {
+    use super::*;
+    use crate::{Header, ValidatorStake};
+
+    const MAINNET_CHECKPOINT: &str = include_str!("../../fixtures/main_0.json");
+    const TESTNET_CHECKPOINT: &str = include_str!("../../fixtures/test_0.json");
+
+    /// A recorded light client block, as stored in `fixtures/`.
+    #[derive(Deserialize)]
+    struct CheckpointFile {
+        body: near_primitives::views::LightClientBlockView,
+    }
+
+    impl TrustedState {
+        /// Trust a light client block and the next block producers it carries.
+        pub fn from_light_client_block(
+            block: near_primitives::views::LightClientBlockView,
+        ) -> Option<Self> {
+            let epoch_bps: Vec<ValidatorStake> =
+                block.next_bps?.into_iter().map(Into::into).collect();
+            let head = Header {
+                prev_block_hash: block.prev_block_hash.0,
+                inner_rest_hash: block.inner_rest_hash.0,
+                inner_lite: block.inner_lite.into(),
+            };
+            Some(Self::new(head, epoch_bps))
+        }
+
+        /// Load a checkpoint in the `{ last_block_hash, body }` form of `fixtures/`.
+        pub fn from_checkpoint_json(json: &str) -> Result<Self, std::io::Error> {
+            let file: CheckpointFile = serde_json::from_str(json)?;
+            Self::from_light_client_block(file.body).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "checkpoint block has no next_bps",
+                )
+            })
+        }
+    }
+
+    impl Network {
+        /// The checkpoint embedded for the preset, or the one given for a custom
+        /// network. Localnet has none, as its genesis differs from run to run.
+        pub fn checkpoint(&self) -> Option<TrustedState> {
+            // The embedded checkpoints are checked to parse by the tests
+            match self {
+                Self::Mainnet => Some(
+                    TrustedState::from_checkpoint_json(MAINNET_CHECKPOINT)
+                        .expect("embedded mainnet checkpoint"),
+                ),
+                Self::Testnet => Some(
+                    TrustedState::from_checkpoint_json(TESTNET_CHECKPOINT)
+                        .expect("embedded testnet checkpoint"),
+                ),
+                Self::Localnet => None,
+                Self::Custom { checkpoint, .. } => Some(*checkpoint.clone()),
+            }
+        }
+
+        /// A custom network starting from the checkpoint file at `path`.
+        pub fn custom_from_file(
+            path: impl AsRef<std::path::Path>,
+            config: ProtocolConfig,
+        ) -> Result<Self, std::io::Error> {
+            let checkpoint = TrustedState::from_checkpoint_json(&std::fs::read_to_string(path)?)?;
+            Ok(Self::Custom {
+                checkpoint: Box::new(checkpoint),
+                config,
+            })
+        }
+    }
+
+    impl core::str::FromStr for Network {
+        type Err = std::io::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "mainnet" => Ok(Self::Mainnet),
+                "testnet" => Ok(Self::Testnet),
+                "localnet" => Ok(Self::Localnet),
+                _ => Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("unknown network: {}", s),
+                )),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::bps_hash;
+
+    #[test]
+    fn test_embedded_checkpoints() {
+        for network in [Network::Mainnet, Network::Testnet] {
+            let checkpoint = network.checkpoint().unwrap();
+            assert_eq!(
+                bps_hash(&checkpoint.epoch_bps),
+                checkpoint.head.inner_lite.next_bp_hash,
+                "{:?}",
+                network
+            );
+        }
+        assert!(Network::Localnet.checkpoint().is_none());
+    }
+}
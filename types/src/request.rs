@@ -0,0 +1,500 @@
+//! Byte encodings of light client requests, as the `req_bytes` inputs of
+//! prover jobs carry them (see `fixtures/sync_proof.json` and
+//! `fixtures/verify_proof.json`):
+//!
+//! ```text
+//! sync:   trusted head hash (32)
+//! verify: trusted head hash (32), then per outcome:
+//!         kind (1: 0 receipt, 1 transaction) | id (32) | account id, padded with `,` (64)
+//! ```
+//!
+//! A verify request names at least one outcome, so the two are told apart by
+//! length.
+//!
+//! Also `ProofBatch`, the Borsh encoding of inclusion proofs under one head in
+//! `fixtures/batch.hex`, which shares path items between the proofs. It's
+//! decode-only: the batch guest and the host take the proofs expanded, as
+//! `inclusion_batch` inputs.
+
+use crate::{combine_hash, hash_borsh, AccountId, BasicProof, Hash, MerklePath, MerklePathItem};
+use alloc::{collections::BTreeMap, vec::Vec};
+use borsh::{BorshDeserialize, BorshSerialize};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// Bytes an account id is padded to. NEAR account ids are at most this long.
+pub const ACCOUNT_ID_LEN: usize = 64;
+/// Pads account ids, and can't appear in one.
+const ACCOUNT_ID_PADDING: u8 = b',';
+const OUTCOME_LEN: usize = 1 + 32 + ACCOUNT_ID_LEN;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestError {
+    /// The input is neither a sync nor a verify request.
+    Length(usize),
+    UnknownKind(u8),
+    /// An account id is not valid UTF-8, or too long to encode.
+    AccountId,
+    /// A batch proof refers to a path item the batch doesn't have.
+    CacheIndex(u32),
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Length(len) => write!(f, "not a request: {} bytes", len),
+            Self::UnknownKind(kind) => write!(f, "unknown outcome kind {}", kind),
+            Self::AccountId => write!(f, "account id doesn't fit the encoding"),
+            Self::CacheIndex(index) => write!(f, "no cached path item {}", index),
+        }
+    }
+}
+
+/// Sync from the trusted head to the next light client block after it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SyncRequest {
+    pub trusted_head: Hash,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeKind {
+    Receipt = 0,
+    Transaction = 1,
+}
+
+/// An outcome to verify: a receipt with its receiver, or a transaction with
+/// its sender.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct OutcomeRequest {
+    pub kind: OutcomeKind,
+    pub id: Hash,
+    pub account_id: AccountId,
+}
+
+/// Verify the inclusion of outcomes under the trusted head.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VerifyRequest {
+    pub trusted_head: Hash,
+    pub outcomes: Vec<OutcomeRequest>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Request {
+    Sync(SyncRequest),
+    Verify(VerifyRequest),
+}
+
+impl Request {
+    pub fn decode(bytes: &[u8]) -> Result<Self, RequestError> {
+        if bytes.len() < 32 || (bytes.len() - 32) % OUTCOME_LEN != 0 {
+            return Err(RequestError::Length(bytes.len()));
+        }
+        let (trusted_head, outcomes) = bytes.split_at(32);
+        let trusted_head = trusted_head.try_into().unwrap();
+        if outcomes.is_empty() {
+            return Ok(Self::Sync(SyncRequest { trusted_head }));
+        }
+
+        let outcomes = outcomes
+            .chunks_exact(OUTCOME_LEN)
+            .map(|outcome| {
+                let kind = match outcome[0] {
+                    0 => OutcomeKind::Receipt,
+                    1 => OutcomeKind::Transaction,
+                    kind => return Err(RequestError::UnknownKind(kind)),
+                };
+                let id = outcome[1..33].try_into().unwrap();
+                let account_id = &outcome[33..];
+                let len = account_id
+                    .iter()
+                    .position(|byte| *byte == ACCOUNT_ID_PADDING)
+                    .unwrap_or(ACCOUNT_ID_LEN);
+                let account_id = core::str::from_utf8(&account_id[..len])
+                    .map_err(|_| RequestError::AccountId)?;
+                Ok(OutcomeRequest {
+                    kind,
+                    id,
+                    account_id: account_id.into(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self::Verify(VerifyRequest {
+            trusted_head,
+            outcomes,
+        }))
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, RequestError> {
+        match self {
+            Self::Sync(SyncRequest { trusted_head }) => Ok(trusted_head.to_vec()),
+            Self::Verify(VerifyRequest {
+                trusted_head,
+                outcomes,
+            }) => {
+                if outcomes.is_empty() {
+                    // Would read back as a sync
+                    return Err(RequestError::Length(32));
+                }
+                let mut bytes = Vec::with_capacity(32 + outcomes.len() * OUTCOME_LEN);
+                bytes.extend_from_slice(trusted_head);
+                for outcome in outcomes {
+                    let account_id = outcome.account_id.as_bytes();
+                    if account_id.len() > ACCOUNT_ID_LEN || account_id.contains(&ACCOUNT_ID_PADDING)
+                    {
+                        return Err(RequestError::AccountId);
+                    }
+                    bytes.push(outcome.kind as u8);
+                    bytes.extend_from_slice(&outcome.id);
+                    bytes.extend_from_slice(account_id);
+                    bytes.resize(
+                        bytes.len() + ACCOUNT_ID_LEN - account_id.len(),
+                        ACCOUNT_ID_PADDING,
+                    );
+                }
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+/// The header hashes an inclusion proof needs: enough to derive the block
+/// hash, and the outcome root the outcome is proven under.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HeaderHashes {
+    pub inner_lite_hash: Hash,
+    pub inner_rest_hash: Hash,
+    pub prev_block_hash: Hash,
+    pub outcome_root: Hash,
+}
+
+impl HeaderHashes {
+    pub fn block_hash(&self) -> Hash {
+        combine_hash(
+            &combine_hash(&self.inner_lite_hash, &self.inner_rest_hash),
+            &self.prev_block_hash,
+        )
+    }
+}
+
+/// An inclusion proof reduced to hashes, with its paths whole.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HashedProof {
+    pub outcome_proof_block_hash: Hash,
+    /// Hash of the outcome and its id, the leaf of `outcome_proof`.
+    pub outcome_hash: Hash,
+    pub outcome_proof: MerklePath,
+    pub outcome_root_proof: MerklePath,
+    pub block_proof: MerklePath,
+    pub header: HeaderHashes,
+}
+
+impl From<&BasicProof> for HashedProof {
+    fn from(proof: &BasicProof) -> Self {
+        let header = &proof.block_header_lite;
+        Self {
+            outcome_proof_block_hash: proof.outcome_proof.block_hash,
+            outcome_hash: hash_borsh(
+                proof
+                    .outcome_proof
+                    .outcome
+                    .to_hashes(proof.outcome_proof.id),
+            ),
+            outcome_proof: proof.outcome_proof.proof.clone(),
+            outcome_root_proof: proof.outcome_root_proof.clone(),
+            block_proof: proof.block_proof.clone(),
+            header: HeaderHashes {
+                inner_lite_hash: header.inner_lite.hash(),
+                inner_rest_hash: header.inner_rest_hash,
+                prev_block_hash: header.prev_block_hash,
+                outcome_root: header.inner_lite.outcome_root,
+            },
+        }
+    }
+}
+
+/// A path item written out, or the index of one in the batch's cache.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum PathEntry {
+    Cached(u32),
+    Item(MerklePathItem),
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BatchProof {
+    pub outcome_proof_block_hash: Hash,
+    pub outcome_hash: Hash,
+    pub outcome_proof: Vec<PathEntry>,
+    pub outcome_root_proof: Vec<PathEntry>,
+    /// The block's path up to where it joins `ancestry`.
+    pub block_proof: Vec<PathEntry>,
+    pub header: HeaderHashes,
+}
+
+/// Inclusion proofs under one head, sharing path items: the block paths'
+/// common tail is kept once as `ancestry`, and every other item used more
+/// than once is kept once in `cache`, in order of first use.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProofBatch {
+    pub head_block_root: Hash,
+    pub batch: Vec<BatchProof>,
+    pub ancestry: MerklePath,
+    pub cache: MerklePath,
+}
+
+/// Key of a path item, as `MerklePathItem` isn't `Ord`.
+fn item_key(item: &MerklePathItem) -> (Hash, u8) {
+    (item.hash, item.direction.clone() as u8)
+}
+
+impl ProofBatch {
+    pub fn new(head_block_root: Hash, proofs: &[HashedProof]) -> Self {
+        let ancestry_len = match proofs.split_first() {
+            Some((first, rest)) => (0..first.block_proof.len())
+                .take_while(|i| {
+                    let item = &first.block_proof[first.block_proof.len() - 1 - i];
+                    rest.iter().all(|proof| {
+                        proof.block_proof.len() > *i
+                            && &proof.block_proof[proof.block_proof.len() - 1 - i] == item
+                    })
+                })
+                .count(),
+            None => 0,
+        };
+        let ancestry = proofs.first().map_or_else(Vec::new, |first| {
+            first.block_proof[first.block_proof.len() - ancestry_len..].to_vec()
+        });
+        let paths = |proof: &HashedProof| {
+            let block_proof = &proof.block_proof[..proof.block_proof.len() - ancestry_len];
+            [
+                proof.outcome_proof.clone(),
+                proof.outcome_root_proof.clone(),
+                block_proof.to_vec(),
+            ]
+        };
+
+        let mut uses = BTreeMap::new();
+        for item in proofs.iter().flat_map(paths).flatten() {
+            *uses.entry(item_key(&item)).or_insert(0) += 1;
+        }
+        let mut cache = Vec::new();
+        let mut cached = BTreeMap::new();
+        let mut entry = |item: MerklePathItem| {
+            if uses[&item_key(&item)] < 2 {
+                return PathEntry::Item(item);
+            }
+            let index = *cached.entry(item_key(&item)).or_insert_with(|| {
+                cache.push(item);
+                cache.len() as u32 - 1
+            });
+            PathEntry::Cached(index)
+        };
+
+        let batch = proofs
+            .iter()
+            .map(|proof| {
+                let [outcome_proof, outcome_root_proof, block_proof] =
+                    paths(proof).map(|path| path.into_iter().map(&mut entry).collect::<Vec<_>>());
+                BatchProof {
+                    outcome_proof_block_hash: proof.outcome_proof_block_hash,
+                    outcome_hash: proof.outcome_hash,
+                    outcome_proof,
+                    outcome_root_proof,
+                    block_proof,
+                    header: proof.header.clone(),
+                }
+            })
+            .collect();
+
+        Self {
+            head_block_root,
+            batch,
+            ancestry,
+            cache,
+        }
+    }
+
+    /// The batch's proofs with their paths whole.
+    pub fn proofs(&self) -> Result<Vec<HashedProof>, RequestError> {
+        let path = |entries: &[PathEntry]| {
+            entries
+                .iter()
+                .map(|entry| match entry {
+                    PathEntry::Item(item) => Ok(item.clone()),
+                    PathEntry::Cached(index) => self
+                        .cache
+                        .get(*index as usize)
+                        .cloned()
+                        .ok_or(RequestError::CacheIndex(*index)),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        self.batch
+            .iter()
+            .map(|proof| {
+                let mut block_proof = path(&proof.block_proof)?;
+                block_proof.extend_from_slice(&self.ancestry);
+                Ok(HashedProof {
+                    outcome_proof_block_hash: proof.outcome_proof_block_hash,
+                    outcome_hash: proof.outcome_hash,
+                    outcome_proof: path(&proof.outcome_proof)?,
+                    outcome_root_proof: path(&proof.outcome_root_proof)?,
+                    block_proof,
+                    header: proof.header.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::compute_root_from_path;
+    use crate::Direction;
+    use alloc::vec;
+
+    #[test]
+    fn test_request_roundtrip() {
+        let sync = Request::Sync(SyncRequest {
+            trusted_head: [1; 32],
+        });
+        assert_eq!(Request::decode(&sync.encode().unwrap()), Ok(sync));
+
+        let verify = Request::Verify(VerifyRequest {
+            trusted_head: [1; 32],
+            outcomes: vec![
+                OutcomeRequest {
+                    kind: OutcomeKind::Receipt,
+                    id: [2; 32],
+                    account_id: "a.testnet".into(),
+                },
+                OutcomeRequest {
+                    kind: OutcomeKind::Transaction,
+                    id: [3; 32],
+                    account_id: "b".repeat(ACCOUNT_ID_LEN),
+                },
+            ],
+        });
+        let bytes = verify.encode().unwrap();
+        assert_eq!(bytes.len(), 32 + 2 * OUTCOME_LEN);
+        assert_eq!(Request::decode(&bytes), Ok(verify));
+
+        assert_eq!(Request::decode(&[0; 33]), Err(RequestError::Length(33)));
+        let mut bytes = bytes;
+        bytes[32] = 2;
+        assert_eq!(Request::decode(&bytes), Err(RequestError::UnknownKind(2)));
+    }
+
+    #[test]
+    fn test_batch_roundtrip() {
+        let item = |byte: u8, direction| MerklePathItem {
+            hash: [byte; 32],
+            direction,
+        };
+        let proof = |leaf: u8, own: u8| HashedProof {
+            outcome_proof_block_hash: [leaf; 32],
+            outcome_hash: [leaf; 32],
+            outcome_proof: vec![item(own, Direction::Left)],
+            outcome_root_proof: vec![item(9, Direction::Right)],
+            block_proof: vec![
+                item(own, Direction::Right),
+                item(7, Direction::Left),
+                item(8, Direction::Left),
+            ],
+            header: HeaderHashes {
+                inner_lite_hash: [leaf; 32],
+                inner_rest_hash: [leaf; 32],
+                prev_block_hash: [leaf; 32],
+                outcome_root: [leaf; 32],
+            },
+        };
+        let proofs = vec![proof(1, 3), proof(2, 4)];
+
+        let batch = ProofBatch::new([0; 32], &proofs);
+        assert_eq!(
+            batch.ancestry,
+            vec![item(7, Direction::Left), item(8, Direction::Left)]
+        );
+        assert_eq!(batch.cache, vec![item(9, Direction::Right)]);
+        assert_eq!(
+            batch.batch[0].outcome_root_proof,
+            vec![PathEntry::Cached(0)]
+        );
+        assert_eq!(batch.proofs(), Ok(proofs));
+    }
+
+    #[cfg(feature = "std")]
+    fn fixture(name: &str) -> std::string::String {
+        std::fs::read_to_string(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("../fixtures")
+                .join(name),
+        )
+        .unwrap()
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_decode_fixtures() {
+        let input = |name: &str| {
+            let job: serde_json::Value = serde_json::from_str(&fixture(name)).unwrap();
+            let input = job["proof_request"]["data"]["input"].as_str().unwrap();
+            hex::decode(input.trim_start_matches("0x")).unwrap()
+        };
+
+        let bytes = input("sync_proof.json");
+        let Ok(Request::Sync(sync)) = Request::decode(&bytes) else {
+            panic!("not a sync request");
+        };
+        assert_eq!(sync.trusted_head.to_vec(), bytes);
+
+        let bytes = input("verify_proof.json");
+        let Ok(Request::Verify(verify)) = Request::decode(&bytes) else {
+            panic!("not a verify request");
+        };
+        assert_eq!(Request::Verify(verify.clone()).encode().unwrap(), bytes);
+        assert_eq!(verify.outcomes.len(), 128);
+        // Every outcome is one of those `fixtures/ids.json` lists
+        let ids: Vec<serde_json::Value> = serde_json::from_str(&fixture("ids.json")).unwrap();
+        for outcome in &verify.outcomes {
+            let (kind, id, account) = match outcome.kind {
+                OutcomeKind::Receipt => ("receipt", "receipt_id", "receiver_id"),
+                OutcomeKind::Transaction => ("transaction", "transaction_hash", "sender_id"),
+            };
+            let id_b58 = near_primitives::hash::CryptoHash(outcome.id).to_string();
+            assert!(ids
+                .iter()
+                .any(|i| i["type"] == kind && i[id] == id_b58 && i[account] == outcome.account_id));
+        }
+
+        // The batch is the compact form of its own proofs
+        let bytes = hex::decode(fixture("batch.hex").trim()).unwrap();
+        let batch: ProofBatch = borsh::from_slice(&bytes).unwrap();
+        assert_eq!(borsh::to_vec(&batch).unwrap(), bytes);
+        let proofs = batch.proofs().unwrap();
+        assert_eq!(ProofBatch::new(batch.head_block_root, &proofs), batch);
+
+        let json: serde_json::Value = serde_json::from_str(&fixture("batch.json")).unwrap();
+        let head_block_root: near_primitives::hash::CryptoHash =
+            json["head_block_root"].as_str().unwrap().parse().unwrap();
+        assert_eq!(batch.head_block_root, head_block_root.0);
+        assert_eq!(proofs.len(), json["batch"].as_array().unwrap().len());
+        for proof in &proofs {
+            let block_hash = proof.header.block_hash();
+            assert_eq!(block_hash, proof.outcome_proof_block_hash);
+            assert_eq!(
+                compute_root_from_path(proof.block_proof.iter(), block_hash),
+                batch.head_block_root
+            );
+            let chunk_root = compute_root_from_path(proof.outcome_proof.iter(), proof.outcome_hash);
+            assert_eq!(
+                compute_root_from_path(proof.outcome_root_proof.iter(), hash_borsh(chunk_root)),
+                proof.header.outcome_root
+            );
+        }
+    }
+}